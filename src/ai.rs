@@ -0,0 +1,306 @@
+use std::sync::OnceLock;
+
+use crate::{
+	bitboard::Bitboard,
+	engine::{Board, Direction, EMPTY}
+};
+
+// Heuristic weights, tuned by hand on 4x4 boards
+const EMPTY_WEIGHT: f64 = 270.0;
+const MERGE_WEIGHT: f64 = 700.0;
+const MONOTONICITY_WEIGHT: f64 = 47.0;
+const MONOTONICITY_POWER: f64 = 4.0;
+
+// Keeps `evaluate` positive so that a lost game, scored 0, is always the worst outcome
+const BASE_SCORE: f64 = 200_000.0;
+
+// Spawns less likely than this are not searched any deeper
+const MIN_PROBABILITY: f64 = 0.0001;
+
+// A board the search can look ahead on. 4x4 boards are searched as bitboards, which are much
+// cheaper to copy, slide and score than a `Board`.
+trait Position: Sized {
+	// The position after sliding, or `None` if nothing moved
+	fn slid(&self, direction: Direction) -> Option<Self>;
+
+	fn empty_cells(&self) -> Vec<(usize, usize)>;
+
+	fn with_tile(&self, x: usize, y: usize, value: usize) -> Self;
+
+	fn evaluate(&self) -> f64;
+}
+
+impl Position for Board {
+	fn slid(&self, direction: Direction) -> Option<Self> {
+		let mut next = self.clone();
+
+		if next.slide(direction).moved() { Some(next) } else { None }
+	}
+
+	fn empty_cells(&self) -> Vec<(usize, usize)> {
+		Board::empty_cells(self)
+	}
+
+	fn with_tile(&self, x: usize, y: usize, value: usize) -> Self {
+		let mut next = self.clone();
+		next.set(x, y, value);
+		next
+	}
+
+	fn evaluate(&self) -> f64 {
+		evaluate(self)
+	}
+}
+
+impl Position for Bitboard {
+	fn slid(&self, direction: Direction) -> Option<Self> {
+		let (next, _) = self.slide(direction);
+
+		if next != *self { Some(next) } else { None }
+	}
+
+	fn empty_cells(&self) -> Vec<(usize, usize)> {
+		Bitboard::empty_cells(self)
+	}
+
+	fn with_tile(&self, x: usize, y: usize, value: usize) -> Self {
+		let mut next = *self;
+		next.set(x, y, value);
+		next
+	}
+
+	fn evaluate(&self) -> f64 {
+		evaluate_bitboard(self)
+	}
+}
+
+// Expectimax search over the player's moves and the random spawns that follow them
+#[derive(Debug, Clone, Copy)]
+pub struct Expectimax {
+	depth: usize
+}
+
+impl Expectimax {
+	// `depth` is the number of moves to look ahead, each followed by a spawn
+	pub fn new(depth: usize) -> Self {
+		Expectimax { depth: depth.max(1) }
+	}
+
+	pub fn depth(&self) -> usize {
+		self.depth
+	}
+
+	// The move with the best expected outcome, or `None` if no move changes the board
+	pub fn best_move(&self, board: &Board) -> Option<Direction> {
+		self.score_moves(board)
+			.into_iter()
+			.max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+			.map(|(direction, _)| direction)
+	}
+
+	// Expected score of every move that changes the board
+	pub fn score_moves(&self, board: &Board) -> Vec<(Direction, f64)> {
+		let values = board.spawn_policy().value_probabilities();
+
+		// Bitboards can't hold tiles above 32768, so boards close to them are searched as they are
+		let fits = |value: usize| value < Bitboard::MAX_VALUE;
+		let bitboard = Bitboard::from_board(board)
+			.filter(|bitboard| bitboard.max_tile().map_or(true, fits))
+			.filter(|_| values.iter().all(|&(value, _)| fits(value)));
+
+		match bitboard {
+			Some(bitboard) => self.score_position(&bitboard, &values),
+			None => self.score_position(board, &values)
+		}
+	}
+
+	// `values` are the spawned exponents and their probabilities
+	fn score_position<P: Position>(&self, position: &P, values: &[(usize, f64)]) -> Vec<(Direction, f64)> {
+		Direction::ALL
+			.iter()
+			.filter_map(|&direction| {
+				position.slid(direction).map(|next| (direction, self.chance(&next, values, self.depth, 1.0)))
+			})
+			.collect()
+	}
+
+	// Average over every tile that could spawn, assuming the cell is picked uniformly
+	fn chance<P: Position>(&self, position: &P, values: &[(usize, f64)], depth: usize, probability: f64) -> f64 {
+		let empty = position.empty_cells();
+
+		if depth == 0 || probability < MIN_PROBABILITY || empty.is_empty() {
+			return position.evaluate();
+		}
+
+		let cell_probability = 1.0 / empty.len() as f64;
+		let mut score = 0.0;
+
+		for &(x, y) in &empty {
+			for &(value, value_probability) in values {
+				let spawn_probability = cell_probability * value_probability;
+				let next = position.with_tile(x, y, value);
+
+				score += spawn_probability * self.max(&next, values, depth - 1, probability * spawn_probability);
+			}
+		}
+
+		score
+	}
+
+	// Score of the best move, or 0 if the game is lost
+	fn max<P: Position>(&self, position: &P, values: &[(usize, f64)], depth: usize, probability: f64) -> f64 {
+		if depth == 0 {
+			return position.evaluate();
+		}
+
+		Direction::ALL
+			.iter()
+			.filter_map(|&direction| position.slid(direction))
+			.map(|next| self.chance(&next, values, depth, probability))
+			.fold(0.0, f64::max)
+	}
+}
+
+// Heuristic score of a position. Rewards empty cells, tiles that can merge and rows and columns
+// that steadily increase or decrease.
+pub fn evaluate(board: &Board) -> f64 {
+	let rows = (0..board.height())
+		.map(|y| (0..board.width()).map(|x| rank(board.get(x, y))).collect::<Vec<f64>>());
+	let columns = (0..board.width())
+		.map(|x| (0..board.height()).map(|y| rank(board.get(x, y))).collect::<Vec<f64>>());
+
+	let mut score = BASE_SCORE + EMPTY_WEIGHT * board.empty_cells().len() as f64;
+
+	for line in rows.chain(columns) {
+		score += MERGE_WEIGHT * merges(&line) - MONOTONICITY_WEIGHT * monotonicity_penalty(&line);
+	}
+
+	score
+}
+
+// Same as `evaluate`, with the score of every possible row looked up in a table
+fn evaluate_bitboard(board: &Bitboard) -> f64 {
+	let table = line_scores();
+	let lines = board.rows().iter().chain(board.columns().iter()).map(|&line| table[line as usize]).sum::<f64>();
+
+	BASE_SCORE + EMPTY_WEIGHT * board.empty_count() as f64 + lines
+}
+
+static LINE_SCORES: OnceLock<Vec<f64>> = OnceLock::new();
+
+// Merge and monotonicity score of every row of 4 cells packed like a bitboard row
+fn line_scores() -> &'static [f64] {
+	LINE_SCORES.get_or_init(|| {
+		(0..=u16::MAX)
+			.map(|row| {
+				let line: Vec<f64> = (0..4).map(|i| ((row >> (4 * i)) & 0xf) as f64).collect();
+
+				MERGE_WEIGHT * merges(&line) - MONOTONICITY_WEIGHT * monotonicity_penalty(&line)
+			})
+			.collect()
+	})
+}
+
+// 0 for an empty cell, 1 for a 2 tile, 2 for a 4 tile and so on
+fn rank(value: usize) -> f64 {
+	if value == EMPTY { 0.0 } else { value as f64 + 1.0 }
+}
+
+// Number of pairs of equal tiles that would merge if the line was slid
+fn merges(line: &[f64]) -> f64 {
+	let tiles: Vec<f64> = line.iter().copied().filter(|&rank| rank > 0.0).collect();
+	let mut count = 0.0;
+	let mut i = 1;
+
+	while i < tiles.len() {
+		if tiles[i] == tiles[i - 1] {
+			count += 1.0;
+			i += 2;
+		}
+		else {
+			i += 1;
+		}
+	}
+
+	count
+}
+
+// How far the line is from being monotonic in its best direction
+fn monotonicity_penalty(line: &[f64]) -> f64 {
+	let mut increasing = 0.0;
+	let mut decreasing = 0.0;
+
+	for pair in line.windows(2) {
+		let (a, b) = (pair[0].powf(MONOTONICITY_POWER), pair[1].powf(MONOTONICITY_POWER));
+
+		if a > b {
+			decreasing += a - b;
+		}
+		else {
+			increasing += b - a;
+		}
+	}
+
+	increasing.min(decreasing)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rng::GameRng;
+
+	// Seeded games played a few moves in, with some of them played with larger spawns
+	fn boards() -> Vec<Board> {
+		(0..40)
+			.map(|seed| {
+				let mut board = Board::with_seed(4, 4, seed);
+				board.spawn_tile();
+
+				for i in 0..seed * 3 {
+					board.play(Direction::ALL[(i as usize * 7 + seed as usize) % 4]);
+				}
+
+				board
+			})
+			.collect()
+	}
+
+	fn assert_close(a: f64, b: f64) {
+		assert!((a - b).abs() <= 1e-9 * a.abs().max(1.0), "{} != {}", a, b);
+	}
+
+	#[test]
+	fn bitboard_evaluation_matches_board() {
+		for board in boards() {
+			assert_close(evaluate_bitboard(&Bitboard::from_board(&board).unwrap()), evaluate(&board));
+		}
+	}
+
+	#[test]
+	fn bitboard_search_matches_board() {
+		let ai = Expectimax::new(2);
+
+		for board in boards() {
+			let values = board.spawn_policy().value_probabilities();
+			let bitboard = Bitboard::from_board(&board).unwrap();
+			let expected = ai.score_position(&board, &values);
+			let scores = ai.score_position(&bitboard, &values);
+
+			assert_eq!(scores.len(), expected.len());
+
+			for (&(direction, score), &(expected_direction, expected_score)) in scores.iter().zip(&expected) {
+				assert_eq!(direction, expected_direction);
+				assert_close(score, expected_score);
+			}
+		}
+	}
+
+	#[test]
+	fn other_sizes_are_searched() {
+		let board = Board::with_state(vec![vec![0, 0, EMPTY], vec![1, EMPTY, EMPTY], vec![EMPTY; 3]], 0, 0, 0, GameRng::from_seed(0));
+		let lost = Board::with_state(vec![vec![0, 1, 0], vec![1, 0, 1], vec![0, 1, 0]], 0, 0, 0, GameRng::from_seed(0));
+
+		assert_eq!(Expectimax::new(2).score_moves(&board).len(), 3);
+		assert_eq!(Expectimax::new(2).best_move(&lost), None);
+	}
+}
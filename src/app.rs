@@ -1,167 +1,585 @@
-use piston::input::{RenderEvent, Event, UpdateArgs, UpdateEvent, PressEvent, ReleaseEvent, Button};
-use piston_window::{PistonWindow, Button::Keyboard, Key};
+use piston::input::{RenderEvent, Event, UpdateArgs, UpdateEvent, PressEvent, ReleaseEvent, MouseCursorEvent, Button, MouseButton};
+use piston_window::{PistonWindow, Window, Context, G2d, Button::Keyboard, Key};
 use piston::event_loop::{EventSettings, Events, EventLoop};
 use graphics::*;
+use std::cmp;
 
-use crate::playboard::PlayBoard;
+use twenty::{
+	ai::Expectimax,
+	engine::{Board, Direction},
+	replay::Replay,
+	strategy::{self, Strategy}
+};
+
+use crate::bindings::{Action, Bindings};
+use crate::bot::{Bot, Search};
+use crate::controls::{ControlsEvent, ControlsMenu};
+use crate::gamepad::Gamepad;
+use crate::swipe::Swipe;
+use crate::theme::{self, Theme};
+use crate::options::Options;
+use crate::playboard::{BoardEvent, PlayBoard};
+use crate::user_data::{self, UserData};
+use crate::viewer::ReplayViewer;
 use crate::utils::{
-	FRAME_COUNT_FOR_AVG,
 	RenderContext,
-	Rgba,
-	GlyphsStorage,
-	Direction,
-	KeyMap,
-	multi_line_text
+	LAYOUT_SIZE,
+	multi_line_text,
+	scaled_text
 };
 
-const BG_COLOR: Rgba = [0.733, 0.678, 0.627, 1.0];
-const TEXT_COLOR: Rgba = [0.463, 0.431, 0.400, 1.0];
+// Thickness of the bar drawn next to the board for a hint
+const HINT_WIDTH: f64 = 8.0;
 
 pub struct App {
 	window: PistonWindow,
 	board: PlayBoard,
-	keys: KeyMap
+	bindings: Bindings,
+	// Keys that are down, so holding a key doesn't repeat its action
+	held: Vec<Key>,
+	// Set while the controls screen is open
+	controls_menu: Option<ControlsMenu>,
+	gamepad: Gamepad,
+	swipe: Swipe,
+	// Where the board was last drawn, swipes have to start on it
+	board_rect: [f64; 4],
+	user_data: UserData,
+	seed: Option<u64>,
+	// Board width and height selected in the size menu, if it is open
+	size_menu: Option<(usize, usize)>,
+	// Finds hints and plays instead of the keyboard while autoplay is on
+	bot: Bot,
+	// Move suggested by the AI, cleared on the next key press
+	hint: Option<Direction>,
+	autoplay: bool,
+	// Seconds since the last autoplay move
+	autoplay_timer: f64,
+	// Set when watching a replay instead of playing
+	viewer: Option<ReplayViewer>,
+	// Theme to switch to before the next frame, it needs the window to load its fonts and images
+	theme_change: Option<Theme>
 }
 
 impl App {
-	pub fn new(window: PistonWindow) -> Self {
+	// Play a game, or watch `replay` if there is one
+	pub fn new(window: PistonWindow, options: Options, replay: Option<Replay>) -> Self {
+		let user_data = UserData::load();
+		let viewer = replay.map(ReplayViewer::new);
+
+		let board = match &viewer {
+			Some(viewer) => {
+				let mut board = viewer.start();
+				board.set_highscore(user_data.highscore());
+				board.set_animation_speed(user_data.animation_speed());
+				board
+			},
+			None => {
+				let mut board = App::create_board(&user_data, user_data.start_game(&options));
+				board.set_replay(user_data::resume_replay(board.board()));
+				board
+			}
+		};
+		let bot = Bot::new(
+			Expectimax::new(user_data.ai_depth()),
+			App::create_strategy(&user_data, user_data.autoplay_strategy())
+		);
+
 		App {
-			window: window,
-			board: PlayBoard::new(4),
-			keys: KeyMap::new()
+			window,
+			board,
+			bindings: user_data.bindings(),
+			held: Vec::new(),
+			controls_menu: None,
+			gamepad: Gamepad::new(user_data.gamepad_dead_zone()),
+			swipe: Swipe::new(),
+			board_rect: [0.0; 4],
+			user_data,
+			seed: options.seed,
+			size_menu: None,
+			bot,
+			hint: None,
+			autoplay: false,
+			autoplay_timer: 0.0,
+			viewer,
+			theme_change: None
 		}
 	}
 
-	fn render(&mut self, e: &Event, render_ctx: &mut RenderContext) {
-		// Used for averaging FPS
-		render_ctx.avg.push(render_ctx.dt);
-		if render_ctx.avg.len() >= FRAME_COUNT_FOR_AVG {
-			render_ctx.avg.remove(0);
-		}
+	// Wrap a board in a view, applying the user's settings
+	fn create_board(user_data: &UserData, game: Board) -> PlayBoard {
+		let mut board = PlayBoard::with_board(game);
+		board.set_highscore(user_data.highscore());
+		board.set_undo_depth(user_data.history_depth());
+		board.set_queue_length(user_data.input_queue());
+		board.set_finish_on_input(user_data.finish_animation_on_input());
+		board.set_animation_speed(user_data.animation_speed());
+		board.set_prompt_keys(&user_data.bindings());
 
-		render_ctx.dt = render_ctx.avg.iter().sum::<f64>() / render_ctx.avg.len() as f64;
+		board
+	}
+
+	fn create_strategy(user_data: &UserData, name: &str) -> Box<dyn Strategy> {
+		strategy::by_name(name, user_data.ai_depth()).expect("strategy names come from strategy::NAMES")
+	}
+
+	fn render(&mut self, e: &Event, render_ctx: &mut RenderContext) {
+		let instructions = self.instructions();
 
 		let board = &mut self.board;
+		let size_menu = self.size_menu;
+		let controls_menu = self.controls_menu.as_ref();
+		let bindings = &self.bindings;
+
+		// Fit the board in the window
+		render_ctx.set_board_size(board.board_size());
+		self.board_rect = render_ctx.board_rect(board.board_size());
+
+		let seed = format!("Seed: {}", board.board().seed());
+
+		// Show the replay position, or the bot with the hint or that autoplay is on
+		let hint = self.hint;
+		let status = match (&self.viewer, self.autoplay, hint) {
+			(Some(viewer), _, _) => viewer.status(),
+			(None, true, _) => format!("Bot: {}\nAutoplay on", self.bot.name()),
+			(None, false, Some(direction)) => format!("Bot: {}\nHint: {:?}", self.bot.name(), direction),
+			(None, false, None) if self.bot.searching() == Some(Search::Hint) => format!("Bot: {}\nThinking...", self.bot.name()),
+			(None, false, None) => format!("Bot: {}", self.bot.name())
+		};
 
 		self.window.draw_2d(e, |ctx, gl, device| {
+			let (text_color, accent) = (render_ctx.theme.text, render_ctx.theme.accent);
+
 			// Clear the screen
-			clear(BG_COLOR, gl);
+			clear(render_ctx.theme.background, gl);
+
+			// Text is positioned in the layout, which is scaled to the window
+			let layout = render_ctx.layout_transform(ctx);
+			let scale = render_ctx.layout_scale;
 
 			// Get text transform
-			let transform = ctx.transform
+			let transform = layout
 				.trans(60.0, 70.0);
 
 			// Render title
-			text(TEXT_COLOR, 50, "2048", &mut render_ctx.glyphs.brandon_blk, transform, gl)
-				.expect("Failed to draw text");
+			scaled_text(text_color, 50, "2048", &mut render_ctx.glyphs.text, transform, scale, gl);
 
-			let transform = ctx.transform
+			let transform = layout
 				.trans(15.0, 95.0);
 
 			// Render instructions
 			multi_line_text(
-				TEXT_COLOR,
+				text_color,
 				25,
-				"Combine the tiles by\nsliding the board with\nWASD or arrow keys",
-				&mut render_ctx.glyphs.brandon_blk,
+				&instructions,
+				&mut render_ctx.glyphs.text,
 				transform,
+				scale,
 				gl
 			);
 
+			// Render the replay, hint or autoplay status
+			multi_line_text(accent, 25, &status, &mut render_ctx.glyphs.text, layout.trans(15.0, 385.0), scale, gl);
+
+			// Render the seed of the current game
+			scaled_text(text_color, 12, &seed, &mut render_ctx.glyphs.mono, layout.trans(15.0, LAYOUT_SIZE[1] - 15.0), scale, gl);
+
 			// Render the playing board
 			board.render(render_ctx, ctx, gl);
 
+			// Highlight the side of the board the hint slides towards
+			if let Some(direction) = hint {
+				App::render_hint(direction, board.board_size(), render_ctx, ctx, gl);
+			}
+
+			// Render the board size menu over everything else
+			if let Some(size) = size_menu {
+				App::render_size_menu(size, bindings, render_ctx, ctx, gl);
+			}
+
+			// Render the controls screen over everything else
+			if let Some(menu) = controls_menu {
+				menu.render(bindings, render_ctx, ctx, gl);
+			}
+
 			// Update glyphs before rendering
-			render_ctx.glyphs.fira_code_reg.factory.encoder.flush(device);
-			render_ctx.glyphs.brandon_blk.factory.encoder.flush(device);
+			render_ctx.glyphs.mono.factory.encoder.flush(device);
+			render_ctx.glyphs.text.factory.encoder.flush(device);
 		});
 	}
 
+	// Controls shown next to the board, using the first key bound to each action
+	fn instructions(&self) -> String {
+		let key = |action| self.bindings.first_key(action);
+
+		if self.viewer.is_some() {
+			return format!(
+				"Watching a replay\n\n{} to pause\n{}/{} to step\n{}/{} for speed\n{} to restart",
+				key(Action::Pause),
+				key(Action::MoveLeft),
+				key(Action::MoveRight),
+				key(Action::MoveUp),
+				key(Action::MoveDown),
+				key(Action::Reset)
+			);
+		}
+
+		let undo = if self.board.undo_enabled() {
+			format!("{} to undo, {} to redo", key(Action::Undo), key(Action::Redo))
+		}
+		else {
+			"Ranked game, no undo".to_string()
+		};
+
+		format!(
+			"Combine the tiles by\nsliding the board with\n{}, {}, {} and {}\n\n{}\n{} for board size\n{} for a hint\n{} to toggle autoplay\n{} to change the bot\n{} to change the theme\n{} for controls",
+			key(Action::MoveUp),
+			key(Action::MoveLeft),
+			key(Action::MoveDown),
+			key(Action::MoveRight),
+			undo,
+			key(Action::BoardSize),
+			key(Action::Hint),
+			key(Action::Pause),
+			key(Action::NextBot),
+			key(Action::Theme),
+			key(Action::Controls)
+		)
+	}
+
+	fn render_hint(direction: Direction, board_size: (usize, usize), render_ctx: &mut RenderContext, ctx: Context, gl: &mut G2d) {
+		let [x, y, width, height] = render_ctx.board_rect(board_size);
+		let bar_width = HINT_WIDTH * render_ctx.layout_scale;
+
+		let bar = match direction {
+			Direction::Left => [x - bar_width, y, bar_width, height],
+			Direction::Right => [x + width, y, bar_width, height],
+			Direction::Up => [x, y - bar_width, width, bar_width],
+			Direction::Down => [x, y + height, width, bar_width]
+		};
+
+		rectangle(render_ctx.theme.accent, bar, ctx.transform, gl);
+	}
+
+	fn render_size_menu((width, height): (usize, usize), bindings: &Bindings, render_ctx: &mut RenderContext, ctx: Context, gl: &mut G2d) {
+		let text_color = render_ctx.theme.text;
+
+		// Draw overlay
+		rectangle(render_ctx.theme.overlay, [0.0, 0.0, render_ctx.window_size[0], render_ctx.window_size[1]], ctx.transform, gl);
+
+		// Get text transform
+		let scale = render_ctx.layout_scale;
+		let transform = render_ctx.layout_transform(ctx)
+			.trans(LAYOUT_SIZE[0] / 2.0 - 90.0, 160.0);
+
+		// Menu title
+		scaled_text(text_color, 35, "Board Size", &mut render_ctx.glyphs.text, transform, scale, gl);
+
+		// Selected size
+		scaled_text(text_color, 35, &format!("<  {}x{}  >", width, height), &mut render_ctx.glyphs.text, transform.trans(10.0, 60.0), scale, gl);
+
+		let controls = format!(
+			"{}/{} to change width\n{}/{} to change height\n{} to start a new game\n{} to close",
+			bindings.first_key(Action::MoveLeft),
+			bindings.first_key(Action::MoveRight),
+			bindings.first_key(Action::MoveDown),
+			bindings.first_key(Action::MoveUp),
+			bindings.first_key(Action::Continue),
+			bindings.first_key(Action::BoardSize)
+		);
+
+		// Controls
+		multi_line_text(
+			text_color,
+			25,
+			&controls,
+			&mut render_ctx.glyphs.text,
+			transform.trans(-45.0, 120.0),
+			scale,
+			gl
+		);
+	}
+
 	fn update(&mut self, args: &UpdateArgs) {
 		// Update playing board
 		self.board.update(args);
+
+		// Replays only move the board, they don't count towards the statistics
+		if let Some(viewer) = &mut self.viewer {
+			viewer.update(&mut self.board, args.dt);
+			self.board.take_events();
+			return;
+		}
+
+		// Show a hint or play the strategy's move once the bot has found it
+		match self.bot.poll(self.board.board()) {
+			Some((Search::Hint, direction)) => self.hint = direction,
+			Some((Search::Move, direction)) if self.autoplay => {
+				let moved = match direction {
+					Some(direction) => self.board.slide(direction),
+					None => false
+				};
+
+				if !moved {
+					self.autoplay = false;
+				}
+			},
+			_ => ()
+		}
+
+		// Let the strategy play, stopping once the game is won or over or the strategy gives up
+		if self.autoplay {
+			if self.board.is_won() || self.board.is_game_over() {
+				self.autoplay = false;
+				self.bot.cancel();
+			}
+			else if self.board.is_idle() && self.bot.searching().is_none() {
+				self.autoplay_timer += args.dt;
+
+				if self.autoplay_timer >= 1.0 / self.user_data.autoplay_rate() {
+					self.autoplay_timer = 0.0;
+					self.hint = None;
+					self.bot.search(Search::Move, self.board.board());
+				}
+			}
+		}
+
+		// Record wins, continues and losses in the statistics
+		let events = self.board.take_events();
+		for event in &events {
+			self.user_data.record(*event);
+		}
+
+		// Keep the recording of every finished game
+		if events.contains(&BoardEvent::GameOver) {
+			user_data::save_replay(self.board.replay());
+		}
+
+		// Save the high score as soon as it is beaten
+		let new_highscore = self.board.highscore() > self.user_data.highscore();
+		if new_highscore {
+			self.user_data.set_highscore(self.board.highscore());
+		}
+
+		if new_highscore || !events.is_empty() {
+			self.user_data.save();
+		}
 	}
 
 	fn key_press(&mut self, args: &Button) {
-		// Slide board based on key press
-		match *args {
-			Keyboard(Key::Left) | Keyboard(Key::A) => {
-				// Only slide if the key has been released prior to being pressed, prevents holding key
-				if self.keys.left.released {
-					self.keys.left.released = false;
+		let key = match *args {
+			Keyboard(key) => key,
+			// Start a swipe if the board was clicked
+			Button::Mouse(MouseButton::Left) => {
+				self.swipe.press(self.board_rect);
+				return;
+			},
+			_ => return
+		};
+
+		// Holding a key down only counts as a single press
+		if self.held.contains(&key) { return; }
+		self.held.push(key);
+
+		// The controls screen takes all input while it is open, without going through the bindings
+		if let Some(menu) = &mut self.controls_menu {
+			match menu.key_press(key, &mut self.bindings) {
+				ControlsEvent::Changed => {
+					self.board.set_prompt_keys(&self.bindings);
+					self.user_data.set_bindings(&self.bindings);
+					self.user_data.save();
+				},
+				ControlsEvent::Closed => self.controls_menu = None,
+				ControlsEvent::None => ()
+			}
+
+			return;
+		}
+
+		if let Some(action) = self.bindings.action(key) {
+			self.action(action);
+		}
+	}
+
+	// Run an action from the keyboard or a gamepad
+	fn action(&mut self, action: Action) {
+		match action {
+			// Quitting, the controls screen and themes work everywhere
+			Action::Quit => self.window.set_should_close(true),
+			Action::Controls => self.controls_menu = Some(ControlsMenu::new()),
+			// Switch to the next theme and remember it for next time
+			Action::Theme => {
+				let id = theme::next(self.user_data.theme());
+
+				self.theme_change = Some(Theme::load(&id));
+				self.user_data.set_theme(&id);
+				self.user_data.save();
+			},
+			// The size menu and the replay viewer take all other input
+			_ => match (self.size_menu, &self.viewer) {
+				(Some(size), _) => self.size_menu_action(size, action),
+				(None, Some(_)) => self.viewer_action(action),
+				(None, None) => self.game_action(action)
+			}
+		}
+	}
 
-					self.board.slide(Direction::Left);
+	fn game_action(&mut self, action: Action) {
+		// Any action makes the last hint outdated
+		self.hint = None;
+
+		match action {
+			Action::MoveLeft => {
+				self.board.slide(Direction::Left);
+			},
+			Action::MoveRight => {
+				self.board.slide(Direction::Right);
+			},
+			Action::MoveUp => {
+				self.board.slide(Direction::Up);
+			},
+			Action::MoveDown => {
+				self.board.slide(Direction::Down);
+			},
+			Action::Undo => self.board.undo(),
+			Action::Redo => self.board.redo(),
+			// Keep playing after winning
+			Action::Continue => self.board.keep_playing(),
+			// Ask the AI for a hint, it is shown once the search is done
+			Action::Hint => {
+				if !self.autoplay {
+					self.bot.search(Search::Hint, self.board.board());
 				}
 			},
-			Keyboard(Key::Right) | Keyboard(Key::D) => {
-				if self.keys.right.released {
-					self.keys.right.released = false;
+			// Toggle autoplay
+			Action::Pause => {
+				self.autoplay = !self.autoplay;
+				self.autoplay_timer = 0.0;
+				self.bot.cancel();
 
-					self.board.slide(Direction::Right);
+				if self.autoplay {
+					self.bot.new_game(self.board.board());
 				}
 			},
-			Keyboard(Key::Up) | Keyboard(Key::W) => {
-				if self.keys.up.released {
-					self.keys.up.released = false;
+			// Switch to the next built-in strategy and remember it for next time
+			Action::NextBot => {
+				let current = strategy::NAMES.iter().position(|&name| name == self.bot.name()).unwrap_or(0);
+				let name = strategy::NAMES[(current + 1) % strategy::NAMES.len()];
+
+				self.bot.set_strategy(App::create_strategy(&self.user_data, name), self.board.board());
 
-					self.board.slide(Direction::Up);
+				self.user_data.set_autoplay_strategy(name);
+				self.user_data.save();
+			},
+			Action::BoardSize => {
+				self.size_menu = Some(self.board.board_size());
+			},
+			Action::Reset => {
+				// Finished games were recorded when they ended
+				if !self.board.is_game_over() {
+					user_data::save_replay(self.board.replay());
 				}
+
+				self.board.reset(self.seed);
 			},
-			Keyboard(Key::Down) | Keyboard(Key::S) => {
-				if self.keys.down.released {
-					self.keys.down.released = false;
+			Action::Theme | Action::Controls | Action::Quit => ()
+		}
+	}
+
+	fn size_menu_action(&mut self, size: (usize, usize), action: Action) {
+		let (width, height) = size;
 
-					self.board.slide(Direction::Down);
+		match action {
+			Action::MoveLeft => {
+				self.size_menu = Some((cmp::max(width - 1, PlayBoard::MIN_SIZE), height));
+			},
+			Action::MoveRight => {
+				self.size_menu = Some((cmp::min(width + 1, PlayBoard::MAX_SIZE), height));
+			},
+			Action::MoveDown => {
+				self.size_menu = Some((width, cmp::max(height - 1, PlayBoard::MIN_SIZE)));
+			},
+			Action::MoveUp => {
+				self.size_menu = Some((width, cmp::min(height + 1, PlayBoard::MAX_SIZE)));
+			},
+			// Start a new game with the selected size and remember it for next time
+			Action::Continue => {
+				if !self.board.is_game_over() {
+					user_data::save_replay(self.board.replay());
 				}
+
+				let mut board = App::create_board(&self.user_data, self.user_data.new_game(size, self.seed));
+				board.set_highscore(self.board.highscore());
+				self.board = board;
+
+				self.user_data.set_board_size(size);
+				self.user_data.save();
+
+				self.size_menu = None;
+			},
+			Action::BoardSize => {
+				self.size_menu = None;
 			},
-			// Reset game on SPACEBAR press
-			Keyboard(Key::Space) => {
-				self.board.reset();
+			_ => ()
+		}
+	}
+
+	fn viewer_action(&mut self, action: Action) {
+		let viewer = match &mut self.viewer {
+			Some(viewer) => viewer,
+			None => return
+		};
+
+		match action {
+			Action::Pause => viewer.toggle_pause(),
+			// Stepping pauses the replay so the move can be looked at
+			Action::MoveRight => {
+				viewer.pause();
+				viewer.step_forward(&mut self.board);
+			},
+			Action::MoveLeft => {
+				viewer.pause();
+				viewer.step_back(&mut self.board);
 			},
+			Action::MoveUp => viewer.faster(),
+			Action::MoveDown => viewer.slower(),
+			Action::Reset => viewer.seek(&mut self.board, 0),
 			_ => ()
 		}
 	}
 
 	fn key_release(&mut self, args: &Button) {
-		// Set pressed key to released
 		match *args {
-			Keyboard(Key::Left)  | Keyboard(Key::A) => self.keys.left.released = true,
-			Keyboard(Key::Right) | Keyboard(Key::D) => self.keys.right.released = true,
-			Keyboard(Key::Up)    | Keyboard(Key::W) => self.keys.up.released = true,
-			Keyboard(Key::Down)  | Keyboard(Key::S) => self.keys.down.released = true,
+			Keyboard(key) => self.held.retain(|&held| held != key),
+			// Finish a swipe, which works like the move keys
+			Button::Mouse(MouseButton::Left) => {
+				if let Some(direction) = self.swipe.release() {
+					if self.controls_menu.is_none() {
+						self.action(Action::slide(direction));
+					}
+				}
+			},
 			_ => ()
 		}
 	}
 
 	pub fn init(&mut self) {
-		// Load ttf fonts
-		let fcreg = self.window.load_font("./assets/FiraCode-Regular.ttf")
-			.expect("Could not load font asset");
-		let bblk = self.window.load_font("./assets/Brandon_blk.ttf")
-			.expect("Could not load font asset");
-
-		// Create a new render context
-		let mut render_ctx = RenderContext::new(&mut self.window, GlyphsStorage {
-			fira_code_reg: fcreg,
-			brandon_blk: bblk
-		});
-
-		// Store the board size offset in the render context
-		render_ctx.board_size = [self.board.board_size() as f64 * -15.0, self.board.board_size() as f64 * -41.0];
+		// Create a new render context, loading the fonts and tile images of the theme
+		let theme = Theme::load(self.user_data.theme());
+		let mut render_ctx = RenderContext::new(&mut self.window, theme);
 
 		// Start event handler at 60 UPS
 		let mut events = Events::new(EventSettings::new().ups(60));
 
-		// Spawn 1 tile on the board
-		self.board.spawn_tile();
-
 		// Initiate events
     	while let Some(e) = events.next(&mut self.window) {
 		// while let Some(e) = self.window.next() {
+			if let Some(theme) = self.theme_change.take() {
+				render_ctx.set_theme(&mut self.window, theme);
+			}
+
 			if let Some(args) = e.render_args() {
 				// Update render context
-				render_ctx.dt = args.ext_dt;
-				render_ctx.window_size = args.window_size;
+				render_ctx.set_window_size(args.window_size);
 
 				// Render app
 				self.render(&e, &mut render_ctx);
@@ -170,6 +588,21 @@ impl App {
 			if let Some(args) = e.update_args() {
 				// Update app
 				self.update(&args);
+
+				// Gamepads can't be used on the controls screen, it is only navigated with the keyboard
+				self.gamepad.poll();
+				for action in self.gamepad.take_actions() {
+					if self.controls_menu.is_none() {
+						self.action(action);
+					}
+				}
+			}
+
+			// Register controller events from window backends that report them
+			self.gamepad.event(&e);
+
+			if let Some(position) = e.mouse_cursor_args() {
+				self.swipe.move_cursor(position);
 			}
 
 			if let Some(args) = e.press_args() {
@@ -181,5 +614,18 @@ impl App {
 				self.key_release(&args);
 			}
 		}
+
+		// Watching a replay leaves the saved game alone
+		if self.viewer.is_some() { return; }
+
+		// Save the game so it can be resumed next time, finished games are not resumed
+		user_data::save_unfinished_replay(self.board.replay());
+
+		if self.board.is_game_over() {
+			user_data::save_game(None);
+		}
+		else {
+			user_data::save_game(Some(self.board.board()));
+		}
 	}
 }
\ No newline at end of file
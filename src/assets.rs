@@ -0,0 +1,83 @@
+use std::{env, path::{Path, PathBuf}, sync::OnceLock};
+
+// Environment variable naming the asset directory
+pub const ASSETS_VAR: &str = "TWENTY_ASSETS";
+
+const ASSETS_DIR: &str = "assets";
+
+static DIRECTORY: OnceLock<PathBuf> = OnceLock::new();
+
+// Fonts of the default theme built into the binary, used when their files are missing
+#[cfg(feature = "embed-assets")]
+const EMBEDDED_FONTS: [(&str, &[u8]); 2] = [
+	("Brandon_blk.ttf", include_bytes!("../assets/Brandon_blk.ttf")),
+	("FiraCode-Regular.ttf", include_bytes!("../assets/FiraCode-Regular.ttf"))
+];
+
+// Use the directory given on the command line, or look for one. Has to be called before any asset
+// is loaded, fails if the given directory does not exist.
+pub fn init(directory: Option<&Path>) -> Result<(), String> {
+	let directory = match directory {
+		Some(directory) if directory.is_dir() => directory.to_path_buf(),
+		Some(directory) => return Err(format!("Asset directory {} does not exist", directory.display())),
+		None => find_directory()
+	};
+
+	DIRECTORY.set(directory).map_err(|_| "The asset directory was already chosen".to_string())
+}
+
+// Directory assets and user data are loaded from
+pub fn directory() -> &'static Path {
+	DIRECTORY.get_or_init(find_directory)
+}
+
+// Path of a file in the asset directory
+pub fn path<P: AsRef<Path>>(file: P) -> PathBuf {
+	directory().join(file)
+}
+
+// Font built into the binary with the given file name, if there is one
+#[cfg(feature = "embed-assets")]
+pub fn embedded_font(name: &str) -> Option<&'static [u8]> {
+	EMBEDDED_FONTS.iter().find(|(file, _)| *file == name).map(|(_, bytes)| *bytes)
+}
+
+#[cfg(not(feature = "embed-assets"))]
+pub fn embedded_font(_name: &str) -> Option<&'static [u8]> {
+	None
+}
+
+// The first existing directory out of the environment variable, the directory next to the
+// executable and the working directory. The working directory is used if there is none.
+fn find_directory() -> PathBuf {
+	let mut candidates = Vec::new();
+
+	if let Some(directory) = env::var_os(ASSETS_VAR).map(PathBuf::from) {
+		if !directory.is_dir() {
+			println!("{} is set to {}, which does not exist", ASSETS_VAR, directory.display());
+		}
+
+		candidates.push(directory);
+	}
+
+	if let Some(directory) = env::current_exe().ok().and_then(|exe| exe.parent().map(|parent| parent.join(ASSETS_DIR))) {
+		candidates.push(directory);
+	}
+
+	let working_directory = PathBuf::from(".").join(ASSETS_DIR);
+	candidates.push(working_directory.clone());
+
+	match candidates.iter().find(|directory| directory.is_dir()) {
+		Some(directory) => directory.clone(),
+		None => {
+			let searched: Vec<String> = candidates.iter().map(|directory| directory.display().to_string()).collect();
+			println!(
+				"No asset directory found, looked in {}. Set it with --assets <path> or {}.",
+				searched.join(", "),
+				ASSETS_VAR
+			);
+
+			working_directory
+		}
+	}
+}
@@ -0,0 +1,247 @@
+use piston_window::Key;
+
+use twenty::engine::Direction;
+
+// Everything a key can be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+	MoveLeft,
+	MoveRight,
+	MoveUp,
+	MoveDown,
+	Undo,
+	Redo,
+	Reset,
+	Continue,
+	Hint,
+	Pause,
+	NextBot,
+	BoardSize,
+	Theme,
+	Controls,
+	Quit
+}
+
+impl Action {
+	pub const ALL: [Action; 15] = [
+		Action::MoveLeft,
+		Action::MoveRight,
+		Action::MoveUp,
+		Action::MoveDown,
+		Action::Undo,
+		Action::Redo,
+		Action::Reset,
+		Action::Continue,
+		Action::Hint,
+		Action::Pause,
+		Action::NextBot,
+		Action::BoardSize,
+		Action::Theme,
+		Action::Controls,
+		Action::Quit
+	];
+
+	// The action sliding the board in a direction
+	pub fn slide(direction: Direction) -> Action {
+		match direction {
+			Direction::Left => Action::MoveLeft,
+			Direction::Right => Action::MoveRight,
+			Direction::Up => Action::MoveUp,
+			Direction::Down => Action::MoveDown
+		}
+	}
+
+	// Key of the action in the user data file
+	pub fn name(&self) -> &'static str {
+		match self {
+			Action::MoveLeft => "move_left",
+			Action::MoveRight => "move_right",
+			Action::MoveUp => "move_up",
+			Action::MoveDown => "move_down",
+			Action::Undo => "undo",
+			Action::Redo => "redo",
+			Action::Reset => "reset",
+			Action::Continue => "continue",
+			Action::Hint => "hint",
+			Action::Pause => "pause",
+			Action::NextBot => "next_bot",
+			Action::BoardSize => "board_size",
+			Action::Theme => "theme",
+			Action::Controls => "controls",
+			Action::Quit => "quit"
+		}
+	}
+
+	// Description shown on the controls screen
+	pub fn label(&self) -> &'static str {
+		match self {
+			Action::MoveLeft => "Move left",
+			Action::MoveRight => "Move right",
+			Action::MoveUp => "Move up",
+			Action::MoveDown => "Move down",
+			Action::Undo => "Undo",
+			Action::Redo => "Redo",
+			Action::Reset => "New game",
+			Action::Continue => "Keep playing",
+			Action::Hint => "Hint",
+			Action::Pause => "Autoplay / pause",
+			Action::NextBot => "Change bot",
+			Action::BoardSize => "Board size",
+			Action::Theme => "Change theme",
+			Action::Controls => "Controls",
+			Action::Quit => "Quit"
+		}
+	}
+
+	fn default_keys(&self) -> Vec<Key> {
+		match self {
+			Action::MoveLeft => vec![Key::A, Key::Left],
+			Action::MoveRight => vec![Key::D, Key::Right],
+			Action::MoveUp => vec![Key::W, Key::Up],
+			Action::MoveDown => vec![Key::S, Key::Down],
+			Action::Undo => vec![Key::Z, Key::Backspace],
+			Action::Redo => vec![Key::Y],
+			Action::Reset => vec![Key::Space],
+			Action::Continue => vec![Key::Return],
+			Action::Hint => vec![Key::H],
+			Action::Pause => vec![Key::P],
+			Action::NextBot => vec![Key::C],
+			Action::BoardSize => vec![Key::M],
+			Action::Theme => vec![Key::T],
+			Action::Controls => vec![Key::K],
+			Action::Quit => vec![Key::Escape]
+		}
+	}
+
+	fn index(&self) -> usize {
+		Action::ALL.iter().position(|action| action == self).expect("every action is in Action::ALL")
+	}
+}
+
+// Keys bound to every action. A key belongs to at most one action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bindings {
+	// Keys of every action, in the order of `Action::ALL`
+	keys: Vec<Vec<Key>>
+}
+
+impl Default for Bindings {
+	fn default() -> Self {
+		Bindings {
+			keys: Action::ALL.iter().map(|action| action.default_keys()).collect()
+		}
+	}
+}
+
+impl Bindings {
+	// The action a key is bound to, if any
+	pub fn action(&self, key: Key) -> Option<Action> {
+		Action::ALL.iter().copied().find(|action| self.keys(*action).contains(&key))
+	}
+
+	pub fn keys(&self, action: Action) -> &[Key] {
+		&self.keys[action.index()]
+	}
+
+	// Replace every key of an action
+	pub fn set(&mut self, action: Action, keys: Vec<Key>) {
+		for &key in &keys {
+			self.unbind(key);
+		}
+
+		self.keys[action.index()] = keys;
+	}
+
+	// Add a key to an action, taking it away from any other action
+	pub fn bind(&mut self, action: Action, key: Key) {
+		self.unbind(key);
+		self.keys[action.index()].push(key);
+	}
+
+	pub fn clear(&mut self, action: Action) {
+		self.keys[action.index()].clear();
+	}
+
+	fn unbind(&mut self, key: Key) {
+		for keys in &mut self.keys {
+			keys.retain(|&bound| bound != key);
+		}
+	}
+
+	// Every key of an action, such as "A, Left"
+	pub fn key_names(&self, action: Action) -> String {
+		self.keys(action)
+			.iter()
+			.map(|&key| key_name(key))
+			.collect::<Vec<String>>()
+			.join(", ")
+	}
+
+	// The first key of an action for instructions, or "-" if it has no keys
+	pub fn first_key(&self, action: Action) -> String {
+		self.keys(action).first().map_or("-".to_string(), |&key| key_name(key))
+	}
+}
+
+pub fn key_name(key: Key) -> String {
+	format!("{:?}", key)
+}
+
+// Find a key by the name `key_name` gives it, ignoring case
+pub fn parse_key(name: &str) -> Option<Key> {
+	// Key codes follow SDL, printable keys use their character and the rest are in a range of their own
+	(0x01..0x80).chain(0x4000_0039..=0x4000_011a)
+		.map(Key::from)
+		.filter(|&key| key != Key::Unknown)
+		.find(|&key| key_name(key).eq_ignore_ascii_case(name))
+}
+
+// Parse a comma separated list of key names, an empty list unbinds the action
+pub fn parse_keys(input: &str) -> Option<Vec<Key>> {
+	input
+		.split(',')
+		.map(str::trim)
+		.filter(|name| !name.is_empty())
+		.map(parse_key)
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_key_lists() {
+		assert_eq!(parse_keys("A, Left"), Some(vec![Key::A, Key::Left]));
+		assert_eq!(parse_keys("space,return , d1"), Some(vec![Key::Space, Key::Return, Key::D1]));
+		assert_eq!(parse_keys("Backspace,"), Some(vec![Key::Backspace]));
+	}
+
+	#[test]
+	fn empty_list_unbinds() {
+		assert_eq!(parse_keys(""), Some(vec![]));
+		assert_eq!(parse_keys(" , "), Some(vec![]));
+	}
+
+	#[test]
+	fn unknown_names_reject_the_list() {
+		assert_eq!(parse_keys("A, Nothing"), None);
+		assert_eq!(parse_keys("Unknown"), None);
+		assert_eq!(parse_keys("Left Right"), None);
+	}
+
+	#[test]
+	fn names_round_trip() {
+		for key in [Key::A, Key::Z, Key::Up, Key::Escape, Key::F12, Key::NumPad8] {
+			assert_eq!(parse_key(&key_name(key)), Some(key));
+		}
+	}
+
+	#[test]
+	fn default_keys_are_written_as_they_are_read() {
+		let bindings = Bindings::default();
+
+		assert_eq!(parse_keys(&bindings.key_names(Action::MoveLeft)), Some(bindings.keys(Action::MoveLeft).to_vec()));
+		assert_eq!(parse_keys(&bindings.key_names(Action::Reset)), Some(bindings.keys(Action::Reset).to_vec()));
+	}
+}
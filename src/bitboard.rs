@@ -0,0 +1,266 @@
+use std::sync::OnceLock;
+
+use crate::{
+	engine::{Board, Direction, Spawn, EMPTY},
+	rng::GameRng,
+	spawn::SpawnPolicy
+};
+
+// A 4x4 board packed into a single `u64` for fast simulation. Each cell takes 4 bits holding its
+// exponent plus one, so 0 is an empty cell, 1 is a 2 tile and 15 is a 32768 tile. Cell (x, y) is
+// stored at bit `4 * (4 * y + x)`, so every row is one `u16` with its leftmost cell in the lowest bits.
+//
+// Slides use precomputed tables of every possible row, and give the same results as `Board::slide`
+// for any board that can be represented. Two 32768 tiles can not be combined since the result does
+// not fit in 4 bits, they are left as they are instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Bitboard(u64);
+
+// Every possible row slid to the left and right, and the score gained by doing so
+struct Tables {
+	left: Vec<u16>,
+	right: Vec<u16>,
+	score: Vec<u32>
+}
+
+static TABLES: OnceLock<Tables> = OnceLock::new();
+
+fn tables() -> &'static Tables {
+	TABLES.get_or_init(|| {
+		let mut tables = Tables {
+			left: vec![0; 1 << 16],
+			right: vec![0; 1 << 16],
+			score: vec![0; 1 << 16]
+		};
+
+		for row in 0..=u16::MAX {
+			let (left, score) = slide_row(row);
+
+			tables.left[row as usize] = left;
+			tables.right[reverse_row(row) as usize] = reverse_row(left);
+			tables.score[row as usize] = score;
+		}
+
+		tables
+	})
+}
+
+// Slide a single row to the left, with the same rules as `Board::slide`
+fn slide_row(row: u16) -> (u16, u32) {
+	let mut cells = [0u16; 4];
+	let mut next = 0;
+	let mut mergeable = false;
+	let mut score = 0;
+
+	for i in 0..4 {
+		let rank = (row >> (4 * i)) & 0xf;
+
+		// Skip if cell is empty
+		if rank == 0 { continue; }
+
+		// Combine with the previous tile if both have the same value and the result fits
+		if mergeable && cells[next - 1] == rank && rank < 15 {
+			cells[next - 1] = rank + 1;
+			score += 2u32.pow(rank as u32 + 1);
+
+			// A tile can only be combined once per move
+			mergeable = false;
+			continue;
+		}
+
+		cells[next] = rank;
+		mergeable = true;
+		next += 1;
+	}
+
+	let packed = cells
+		.iter()
+		.enumerate()
+		.fold(0, |packed, (i, &rank)| packed | rank << (4 * i));
+
+	(packed, score)
+}
+
+fn reverse_row(row: u16) -> u16 {
+	(row >> 12) | ((row >> 4) & 0x00f0) | ((row << 4) & 0x0f00) | (row << 12)
+}
+
+// Swap rows and columns
+fn transpose(board: u64) -> u64 {
+	let a1 = board & 0xf0f0_0f0f_f0f0_0f0f;
+	let a2 = board & 0x0000_f0f0_0000_f0f0;
+	let a3 = board & 0x0f0f_0000_0f0f_0000;
+	let a = a1 | (a2 << 12) | (a3 >> 12);
+
+	let b1 = a & 0xff00_ff00_00ff_00ff;
+	let b2 = a & 0x00ff_00ff_0000_0000;
+	let b3 = a & 0x0000_0000_ff00_ff00;
+
+	b1 | (b2 >> 24) | (b3 << 24)
+}
+
+// Slide all 4 rows of a board using one of the row tables
+fn slide_rows(board: u64, table: &[u16]) -> (u64, u32) {
+	let tables = tables();
+	let mut res = 0;
+	let mut score = 0;
+
+	for y in 0..4 {
+		let row = ((board >> (16 * y)) & 0xffff) as usize;
+
+		res |= (table[row] as u64) << (16 * y);
+		score += tables.score[row];
+	}
+
+	(res, score)
+}
+
+impl Bitboard {
+	// Highest exponent that fits in a cell, a 32768 tile
+	pub const MAX_VALUE: usize = 14;
+
+	pub fn from_raw(raw: u64) -> Self {
+		Bitboard(raw)
+	}
+
+	pub fn raw(&self) -> u64 {
+		self.0
+	}
+
+	// Pack a board, returns `None` if it is not 4x4 or has a tile above 32768
+	pub fn from_board(board: &Board) -> Option<Self> {
+		if board.width() != 4 || board.height() != 4 {
+			return None;
+		}
+
+		let mut res = Bitboard::default();
+
+		for y in 0..4 {
+			for x in 0..4 {
+				let value = board.get(x, y);
+
+				if value != EMPTY && value > Bitboard::MAX_VALUE {
+					return None;
+				}
+
+				res.set(x, y, value);
+			}
+		}
+
+		Some(res)
+	}
+
+	// Unpack into rows of cells, using the same encoding as `Board`
+	pub fn to_cells(&self) -> Vec<Vec<usize>> {
+		(0..4)
+			.map(|y| (0..4).map(|x| self.get(x, y)).collect())
+			.collect()
+	}
+
+	// Exponent of the tile at a position, or `EMPTY`
+	pub fn get(&self, x: usize, y: usize) -> usize {
+		match (self.0 >> (4 * (4 * y + x))) & 0xf {
+			0 => EMPTY,
+			rank => rank as usize - 1
+		}
+	}
+
+	// Set the exponent of a tile, `value` must be `EMPTY` or at most `MAX_VALUE`
+	pub fn set(&mut self, x: usize, y: usize, value: usize) {
+		let shift = 4 * (4 * y + x);
+		let rank = if value == EMPTY { 0 } else { value as u64 + 1 };
+
+		self.0 = (self.0 & !(0xf << shift)) | (rank << shift);
+	}
+
+	// Slide every tile in the given direction, returning the new board and the score gained
+	pub fn slide(&self, direction: Direction) -> (Bitboard, u32) {
+		let tables = tables();
+
+		let (res, score) = match direction {
+			Direction::Left => slide_rows(self.0, &tables.left),
+			Direction::Right => slide_rows(self.0, &tables.right),
+			Direction::Up => {
+				let (res, score) = slide_rows(transpose(self.0), &tables.left);
+				(transpose(res), score)
+			},
+			Direction::Down => {
+				let (res, score) = slide_rows(transpose(self.0), &tables.right);
+				(transpose(res), score)
+			}
+		};
+
+		(Bitboard(res), score)
+	}
+
+	// Spawn a tile in a random empty cell, using the generator the same way `Board::spawn_tile` does.
+	// Returns `None` if the board is full or the value doesn't fit in a cell.
+	pub fn spawn_tile(&mut self, spawn_policy: &dyn SpawnPolicy, rng: &mut GameRng) -> Option<Spawn> {
+		let empty = self.empty_cells();
+
+		if empty.is_empty() {
+			return None;
+		}
+
+		let (x, y) = empty[spawn_policy.choose_cell(&empty, rng)];
+		let value = spawn_policy.choose_value(rng);
+
+		if value > Bitboard::MAX_VALUE {
+			return None;
+		}
+
+		self.set(x, y, value);
+
+		Some(Spawn { x, y, value })
+	}
+
+	// Slide the board and spawn a new tile if anything moved, like `Board::play`. Returns the score
+	// gained and the spawned tile, or `None` if nothing moved.
+	pub fn play(&mut self, direction: Direction, spawn_policy: &dyn SpawnPolicy, rng: &mut GameRng) -> Option<(u32, Option<Spawn>)> {
+		let (slid, score) = self.slide(direction);
+
+		if slid == *self {
+			return None;
+		}
+
+		*self = slid;
+
+		Some((score, self.spawn_tile(spawn_policy, rng)))
+	}
+
+	// Every row packed into a `u16`, top row first
+	pub fn rows(&self) -> [u16; 4] {
+		[0, 1, 2, 3].map(|y| (self.0 >> (16 * y)) as u16)
+	}
+
+	// Every column packed into a `u16` like a row with the top cell first, left column first
+	pub fn columns(&self) -> [u16; 4] {
+		Bitboard(transpose(self.0)).rows()
+	}
+
+	// Number of empty cells
+	pub fn empty_count(&self) -> usize {
+		(0..16).filter(|i| (self.0 >> (4 * i)) & 0xf == 0).count()
+	}
+
+	// Positions of every empty cell, in row order
+	pub fn empty_cells(&self) -> Vec<(usize, usize)> {
+		(0..16)
+			.filter(|i| (self.0 >> (4 * i)) & 0xf == 0)
+			.map(|i| (i % 4, i / 4))
+			.collect()
+	}
+
+	// Exponent of the highest tile on the board, if there are any tiles
+	pub fn max_tile(&self) -> Option<usize> {
+		(0..16)
+			.map(|i| ((self.0 >> (4 * i)) & 0xf) as usize)
+			.max()
+			.filter(|&rank| rank > 0)
+			.map(|rank| rank - 1)
+	}
+
+	pub fn is_game_over(&self) -> bool {
+		Direction::ALL.iter().all(|&direction| self.slide(direction).0 == *self)
+	}
+}
@@ -0,0 +1,141 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use twenty::{
+	ai::Expectimax,
+	engine::{Board, Direction},
+	strategy::Strategy
+};
+
+// What a search is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Search {
+	// A move suggested by the AI
+	Hint,
+	// The next move of the autoplay strategy
+	Move
+}
+
+enum Request {
+	Search(u64, Search, Board),
+	NewGame(Board),
+	Strategy(Box<dyn Strategy>)
+}
+
+// Cells, score and moves of a board, to tell whether it changed since a search started
+type BoardKey = (Vec<Vec<usize>>, u32, u32);
+
+fn board_key(board: &Board) -> BoardKey {
+	(board.cells().clone(), board.score(), board.moves())
+}
+
+// Runs the hint AI and the autoplay strategy on a worker thread. Searches on large boards take
+// seconds, which would otherwise stop the window from drawing and taking input.
+pub struct Bot {
+	requests: Sender<Request>,
+	results: Receiver<(u64, Option<Direction>)>,
+	// Name of the autoplay strategy, which lives on the worker thread
+	name: String,
+	// Id of the last search
+	last_id: u64,
+	// The search being waited for and the board it is for
+	pending: Option<(u64, Search, BoardKey)>
+}
+
+impl Bot {
+	pub fn new(ai: Expectimax, strategy: Box<dyn Strategy>) -> Self {
+		let (requests, worker_requests) = mpsc::channel();
+		let (worker_results, results) = mpsc::channel();
+		let name = strategy.name().to_string();
+
+		thread::spawn(move || Bot::work(ai, strategy, worker_requests, worker_results));
+
+		Bot {
+			requests,
+			results,
+			name,
+			last_id: 0,
+			pending: None
+		}
+	}
+
+	// Runs until the bot is dropped
+	fn work(ai: Expectimax, mut strategy: Box<dyn Strategy>, requests: Receiver<Request>, results: Sender<(u64, Option<Direction>)>) {
+		while let Ok(request) = requests.recv() {
+			let mut queue = vec![request];
+			queue.extend(requests.try_iter());
+
+			// Only the last search is still wanted, the ones before it are for boards that have changed
+			let last_search = queue.iter().rposition(|request| matches!(request, Request::Search(..)));
+
+			for (i, request) in queue.into_iter().enumerate() {
+				match request {
+					Request::Search(id, search, board) if Some(i) == last_search => {
+						let direction = match search {
+							Search::Hint => ai.best_move(&board),
+							Search::Move => strategy.next_move(&board)
+						};
+
+						if results.send((id, direction)).is_err() {
+							return;
+						}
+					},
+					Request::Search(..) => (),
+					Request::NewGame(board) => strategy.new_game(&board),
+					Request::Strategy(new) => strategy = new
+				}
+			}
+		}
+	}
+
+	// Name of the autoplay strategy
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	// Play with another strategy, starting from `board`
+	pub fn set_strategy(&mut self, strategy: Box<dyn Strategy>, board: &Board) {
+		self.name = strategy.name().to_string();
+		self.cancel();
+		self.send(Request::Strategy(strategy));
+		self.new_game(board);
+	}
+
+	// Let the strategy reset its state before it plays `board`
+	pub fn new_game(&mut self, board: &Board) {
+		self.send(Request::NewGame(board.clone()));
+	}
+
+	// Start a search, replacing the one in progress. The result is given by `poll`.
+	pub fn search(&mut self, search: Search, board: &Board) {
+		self.last_id += 1;
+		self.pending = Some((self.last_id, search, board_key(board)));
+		self.send(Request::Search(self.last_id, search, board.clone()));
+	}
+
+	// The search in progress, if there is one
+	pub fn searching(&self) -> Option<Search> {
+		self.pending.as_ref().map(|&(_, search, _)| search)
+	}
+
+	// Stop waiting for the search in progress
+	pub fn cancel(&mut self) {
+		self.pending = None;
+	}
+
+	// The result of the search in progress once it is done. Results for a board other than `board`
+	// are dropped.
+	pub fn poll(&mut self, board: &Board) -> Option<(Search, Option<Direction>)> {
+		let (id, search, _) = self.pending.as_ref()?;
+		let (id, search) = (*id, *search);
+		let (_, direction) = self.results.try_iter().find(|&(result_id, _)| result_id == id)?;
+		let (_, _, key) = self.pending.take()?;
+
+		if key == board_key(board) { Some((search, direction)) } else { None }
+	}
+
+	fn send(&self, request: Request) {
+		// The worker only stops if a strategy panics, the bot just stops answering then
+		let _ = self.requests.send(request);
+	}
+}
@@ -0,0 +1,113 @@
+use piston_window::{Context, G2d, Key};
+use graphics::*;
+
+use crate::bindings::{Action, Bindings};
+use crate::utils::{RenderContext, LAYOUT_SIZE, multi_line_text, scaled_text};
+
+// Height of a single action in the list
+const ROW_HEIGHT: f64 = 24.0;
+
+// What happened to the bindings after a key press on the controls screen
+#[derive(Debug, PartialEq)]
+pub enum ControlsEvent {
+	None,
+	Changed,
+	Closed
+}
+
+// Screen for rebinding the controls. It is always navigated with the arrow keys, ENTER and ESCAPE
+// so it can't be made unusable by a bad binding.
+pub struct ControlsMenu {
+	selected: usize,
+	// Waiting for the key to add to the selected action
+	capturing: bool
+}
+
+impl ControlsMenu {
+	pub fn new() -> Self {
+		ControlsMenu {
+			selected: 0,
+			capturing: false
+		}
+	}
+
+	pub fn key_press(&mut self, key: Key, bindings: &mut Bindings) -> ControlsEvent {
+		let action = Action::ALL[self.selected];
+
+		if self.capturing {
+			self.capturing = false;
+
+			// ESCAPE cancels instead of being bound
+			if key == Key::Escape {
+				return ControlsEvent::None;
+			}
+
+			bindings.bind(action, key);
+			return ControlsEvent::Changed;
+		}
+
+		match key {
+			Key::Up => {
+				self.selected = (self.selected + Action::ALL.len() - 1) % Action::ALL.len();
+			},
+			Key::Down => {
+				self.selected = (self.selected + 1) % Action::ALL.len();
+			},
+			Key::Return => {
+				self.capturing = true;
+			},
+			Key::Delete | Key::Backspace => {
+				bindings.clear(action);
+				return ControlsEvent::Changed;
+			},
+			Key::F5 => {
+				*bindings = Bindings::default();
+				return ControlsEvent::Changed;
+			},
+			Key::Escape => return ControlsEvent::Closed,
+			_ => ()
+		}
+
+		ControlsEvent::None
+	}
+
+	pub fn render(&self, bindings: &Bindings, render_ctx: &mut RenderContext, ctx: Context, gl: &mut G2d) {
+		let (text_color, selected_color) = (render_ctx.theme.text, render_ctx.theme.accent);
+
+		// Draw overlay
+		rectangle(render_ctx.theme.menu_background, [0.0, 0.0, render_ctx.window_size[0], render_ctx.window_size[1]], ctx.transform, gl);
+
+		let scale = render_ctx.layout_scale;
+		let transform = render_ctx.layout_transform(ctx).trans(LAYOUT_SIZE[0] / 2.0 - 250.0, 50.0);
+
+		// Screen title
+		scaled_text(text_color, 30, "Controls", &mut render_ctx.glyphs.text, transform, scale, gl);
+
+		// Every action with its keys, the selected one highlighted
+		for (i, &action) in Action::ALL.iter().enumerate() {
+			let color = if i == self.selected { selected_color } else { text_color };
+			let row = transform.trans(0.0, 40.0 + i as f64 * ROW_HEIGHT);
+
+			let keys = if i == self.selected && self.capturing {
+				"Press a key...".to_string()
+			}
+			else {
+				bindings.key_names(action)
+			};
+
+			scaled_text(color, 20, action.label(), &mut render_ctx.glyphs.text, row, scale, gl);
+			scaled_text(color, 20, &keys, &mut render_ctx.glyphs.text, row.trans(220.0, 0.0), scale, gl);
+		}
+
+		// Controls of this screen
+		multi_line_text(
+			text_color,
+			16,
+			"UP/DOWN to select, ENTER to add a key, DELETE to clear\nF5 to restore the defaults, ESCAPE to close",
+			&mut render_ctx.glyphs.text,
+			transform.trans(0.0, 60.0 + Action::ALL.len() as f64 * ROW_HEIGHT),
+			scale,
+			gl
+		);
+	}
+}
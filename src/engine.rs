@@ -0,0 +1,449 @@
+use std::sync::Arc;
+
+use crate::{
+	rng::{self, GameRng},
+	spawn::{SpawnPolicy, WeightedSpawn}
+};
+
+// Value of a cell that holds no tile
+pub const EMPTY: usize = 99;
+
+// Exponent of the 2048 tile
+pub const DEFAULT_TARGET: usize = 10;
+
+// Smallest and largest boards that can be played
+pub const MIN_SIZE: usize = 3;
+pub const MAX_SIZE: usize = 8;
+
+// Highest exponent a tile can be loaded or spawned with, the 2^27 tile. A game that reaches it has a
+// score close to the largest u32, so saves, replays and spawn weights with higher tiles are invalid.
+// Higher tiles can still be made by merging, scores stop growing at the largest u32 then.
+pub const MAX_VALUE: usize = 26;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+	Left,
+	Right,
+	Up,
+	Down
+}
+
+impl Direction {
+	pub const ALL: [Direction; 4] = [Direction::Left, Direction::Right, Direction::Up, Direction::Down];
+
+	pub fn displacement(&self) -> (isize, isize) {
+		match self {
+			Direction::Left => (-1, 0),
+			Direction::Right => (1, 0),
+			Direction::Up => (0, -1),
+			Direction::Down => (0, 1)
+		}
+	}
+
+	pub fn is_negative(&self) -> bool {
+		match self {
+			Direction::Right | Direction::Down => false,
+			Direction::Left | Direction::Up => true
+		}
+	}
+}
+
+// A single tile moving from one cell to another during a slide
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileMove {
+	pub from: (usize, usize),
+	pub to: (usize, usize),
+	pub value: usize,
+	pub combine: bool
+}
+
+// A tile placed on the board after a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spawn {
+	pub x: usize,
+	pub y: usize,
+	pub value: usize
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MoveOutcome {
+	pub moves: Vec<TileMove>,
+	pub score: u32,
+	pub spawned: Option<Spawn>
+}
+
+impl MoveOutcome {
+	pub fn moved(&self) -> bool {
+		!self.moves.is_empty()
+	}
+}
+
+// The rules of the game without any rendering or animation state. Cell values are stored as
+// exponents, where 0 is a 2 tile, 1 is a 4 tile and so on, and `EMPTY` is an empty cell.
+#[derive(Debug, Clone)]
+pub struct Board {
+	cells: Vec<Vec<usize>>,
+	width: usize,
+	height: usize,
+	score: u32,
+	moves: u32,
+	seed: u64,
+	rng: GameRng,
+	spawn_policy: Arc<dyn SpawnPolicy>,
+	target: usize,
+	continued: bool
+}
+
+impl Board {
+	pub fn new(width: usize, height: usize) -> Self {
+		Board::with_seed(width, height, rng::random_seed())
+	}
+
+	// Create a board whose spawns are fully determined by `seed`
+	pub fn with_seed(width: usize, height: usize, seed: u64) -> Self {
+		Board {
+			cells: vec![vec![EMPTY; width]; height],
+			width,
+			height,
+			score: 0,
+			moves: 0,
+			seed,
+			rng: GameRng::from_seed(seed),
+			spawn_policy: Arc::new(WeightedSpawn::classic()),
+			target: DEFAULT_TARGET,
+			continued: false
+		}
+	}
+
+	// Rebuild a board from previously stored state, such as a save file. `cells` is a list of rows
+	// which must all have the same length, holding values up to `MAX_VALUE`.
+	pub fn with_state(cells: Vec<Vec<usize>>, score: u32, moves: u32, seed: u64, rng: GameRng) -> Self {
+		Board {
+			width: cells.first().map_or(0, |row| row.len()),
+			height: cells.len(),
+			cells,
+			score,
+			moves,
+			seed,
+			rng,
+			spawn_policy: Arc::new(WeightedSpawn::classic()),
+			target: DEFAULT_TARGET,
+			continued: false
+		}
+	}
+
+	pub fn width(&self) -> usize {
+		self.width
+	}
+
+	pub fn height(&self) -> usize {
+		self.height
+	}
+
+	pub fn score(&self) -> u32 {
+		self.score
+	}
+
+	// Number of moves made that changed the board
+	pub fn moves(&self) -> u32 {
+		self.moves
+	}
+
+	// Seed the game was started with
+	pub fn seed(&self) -> u64 {
+		self.seed
+	}
+
+	pub fn rng(&self) -> &GameRng {
+		&self.rng
+	}
+
+	pub fn spawn_policy(&self) -> &dyn SpawnPolicy {
+		self.spawn_policy.as_ref()
+	}
+
+	// Change how new tiles are spawned, the classic 2048 rules are used by default
+	pub fn set_spawn_policy(&mut self, spawn_policy: Arc<dyn SpawnPolicy>) {
+		self.spawn_policy = spawn_policy;
+	}
+
+	pub fn get(&self, x: usize, y: usize) -> usize {
+		self.cells[y][x]
+	}
+
+	pub fn set(&mut self, x: usize, y: usize, value: usize) {
+		self.cells[y][x] = value;
+	}
+
+	pub fn cells(&self) -> &Vec<Vec<usize>> {
+		&self.cells
+	}
+
+	// Exponent of the tile that wins the game
+	pub fn target(&self) -> usize {
+		self.target
+	}
+
+	pub fn set_target(&mut self, target: usize) {
+		self.target = target;
+	}
+
+	// Exponent of the highest tile on the board, if there are any tiles
+	pub fn max_tile(&self) -> Option<usize> {
+		self.cells.iter().flatten().copied().filter(|&value| value != EMPTY).max()
+	}
+
+	// Whether the target tile has been reached and the player has not chosen to keep playing yet
+	pub fn has_won(&self) -> bool {
+		!self.continued && self.max_tile().is_some_and(|value| value >= self.target)
+	}
+
+	// Keep playing after reaching the target tile
+	pub fn keep_playing(&mut self) {
+		self.continued = true;
+	}
+
+	pub fn is_continued(&self) -> bool {
+		self.continued
+	}
+
+	// Positions of every empty cell, in row order
+	pub fn empty_cells(&self) -> Vec<(usize, usize)> {
+		let mut res = Vec::with_capacity(self.width * self.height);
+
+		for y in 0..self.height {
+			for x in 0..self.width {
+				if self.cells[y][x] == EMPTY {
+					res.push((x, y));
+				}
+			}
+		}
+
+		res
+	}
+
+	// Spawn a tile at a random empty location
+	pub fn spawn_tile(&mut self) -> Option<Spawn> {
+		let empty = self.empty_cells();
+
+		if empty.is_empty() {
+			return None;
+		}
+
+		let (x, y) = empty[self.spawn_policy.choose_cell(&empty, &mut self.rng)];
+		let value = self.spawn_policy.choose_value(&mut self.rng);
+
+		self.cells[y][x] = value;
+
+		Some(Spawn { x, y, value })
+	}
+
+	// Cell positions of a single row or column, ordered starting from the edge the tiles slide towards
+	fn line(&self, direction: Direction, index: usize) -> Vec<(usize, usize)> {
+		let length = self.line_length(direction);
+
+		(0..length)
+			.map(|i| if direction.is_negative() { i } else { length - i - 1 })
+			.map(|i| match direction {
+				Direction::Left | Direction::Right => (i, index),
+				Direction::Up | Direction::Down => (index, i)
+			})
+			.collect()
+	}
+
+	// Number of cells in a line along the direction of movement
+	fn line_length(&self, direction: Direction) -> usize {
+		match direction {
+			Direction::Left | Direction::Right => self.width,
+			Direction::Up | Direction::Down => self.height
+		}
+	}
+
+	// Slide every tile in the given direction, combining equal tiles. Does not spawn a new tile.
+	pub fn slide(&mut self, direction: Direction) -> MoveOutcome {
+		let mut outcome = MoveOutcome::default();
+
+		// Rows when sliding sideways, columns when sliding up or down
+		let lines = match direction {
+			Direction::Left | Direction::Right => self.height,
+			Direction::Up | Direction::Down => self.width
+		};
+
+		for index in 0..lines {
+			let line = self.line(direction, index);
+
+			// Next free position in the line and the last placed tile that can still be combined with
+			let mut next = 0;
+			let mut mergeable: Option<(usize, usize)> = None;
+
+			for &(x, y) in &line {
+				let value = self.cells[y][x];
+
+				// Skip if tile is empty
+				if value == EMPTY { continue; }
+
+				// Combine with the previous tile if both have the same value
+				if let Some((mx, my)) = mergeable {
+					if self.cells[my][mx] == value {
+						self.cells[my][mx] = value + 1;
+						self.cells[y][x] = EMPTY;
+						outcome.score = outcome.score.saturating_add(2u32.checked_pow(value as u32 + 2).unwrap_or(u32::MAX));
+						outcome.moves.push(TileMove { from: (x, y), to: (mx, my), value, combine: true });
+
+						// A tile can only be combined once per move
+						mergeable = None;
+						continue;
+					}
+				}
+
+				// Move the tile to the next free position
+				let (nx, ny) = line[next];
+				if (nx, ny) != (x, y) {
+					self.cells[ny][nx] = value;
+					self.cells[y][x] = EMPTY;
+					outcome.moves.push(TileMove { from: (x, y), to: (nx, ny), value, combine: false });
+				}
+
+				mergeable = Some((nx, ny));
+				next += 1;
+			}
+		}
+
+		self.score = self.score.saturating_add(outcome.score);
+
+		if outcome.moved() {
+			self.moves += 1;
+		}
+
+		outcome
+	}
+
+	// Slide the board and spawn a new tile if anything moved
+	pub fn play(&mut self, direction: Direction) -> MoveOutcome {
+		let mut outcome = self.slide(direction);
+
+		if outcome.moved() {
+			outcome.spawned = self.spawn_tile();
+		}
+
+		outcome
+	}
+
+	pub fn is_game_over(&self) -> bool {
+		for y in 0..self.height {
+			for x in 0..self.width {
+				if self.cells[y][x] == EMPTY {
+					return false;
+				}
+
+				// Check for a match to the right
+				if x < self.width - 1 && self.cells[y][x] == self.cells[y][x + 1] {
+					return false;
+				}
+
+				// Check for a match below
+				if y < self.height - 1 && self.cells[y][x] == self.cells[y + 1][x] {
+					return false;
+				}
+			}
+		}
+
+		true
+	}
+
+	// Clear the board, reset the score and spawn 1 random tile
+	pub fn reset(&mut self) -> Option<Spawn> {
+		self.reset_with_seed(rng::random_seed())
+	}
+
+	// Same as `reset`, but the new game is played with the given seed
+	pub fn reset_with_seed(&mut self, seed: u64) -> Option<Spawn> {
+		for row in &mut self.cells {
+			for cell in row {
+				*cell = EMPTY;
+			}
+		}
+
+		self.score = 0;
+		self.moves = 0;
+		self.continued = false;
+		self.seed = seed;
+		self.rng = GameRng::from_seed(seed);
+		self.spawn_tile()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const E: usize = EMPTY;
+
+	fn board(cells: Vec<Vec<usize>>) -> Board {
+		Board::with_state(cells, 0, 0, 0, GameRng::from_seed(0))
+	}
+
+	// Slide a single row to the left, values are exponents
+	fn slide_row(row: Vec<usize>) -> (Vec<usize>, u32) {
+		let mut board = board(vec![row]);
+		let outcome = board.slide(Direction::Left);
+
+		(board.cells()[0].clone(), outcome.score)
+	}
+
+	#[test]
+	fn four_equal_tiles_make_two_pairs() {
+		assert_eq!(slide_row(vec![0, 0, 0, 0]), (vec![1, 1, E, E], 8));
+	}
+
+	#[test]
+	fn first_pair_towards_the_edge_combines() {
+		assert_eq!(slide_row(vec![0, 0, 1, E]), (vec![1, 1, E, E], 4));
+		assert_eq!(slide_row(vec![1, 0, 0, E]), (vec![1, 1, E, E], 4));
+	}
+
+	#[test]
+	fn tiles_combine_once_per_move() {
+		assert_eq!(slide_row(vec![1, 1, 2, E]), (vec![2, 2, E, E], 8));
+		assert_eq!(slide_row(vec![0, 0, 1, 1]), (vec![1, 2, E, E], 12));
+		assert_eq!(slide_row(vec![E, 0, E, 0]), (vec![1, E, E, E], 4));
+		assert_eq!(slide_row(vec![0, 1, 0, 1]), (vec![0, 1, 0, 1], 0));
+	}
+
+	#[test]
+	fn score_stops_at_the_largest_u32() {
+		let mut board = Board::with_state(vec![vec![40, 40]], u32::MAX - 1, 0, 0, GameRng::from_seed(0));
+		let outcome = board.slide(Direction::Left);
+
+		assert_eq!(board.cells(), &vec![vec![41, E]]);
+		assert_eq!(outcome.score, u32::MAX);
+		assert_eq!(board.score(), u32::MAX);
+	}
+
+	#[test]
+	fn rectangular_board_slides_every_direction() {
+		let cells = vec![
+			vec![0, E, 0],
+			vec![1, 0, E],
+			vec![1, 2, 0],
+			vec![E, 2, 1]
+		];
+
+		let expected = [
+			(Direction::Left, vec![vec![1, E, E], vec![1, 0, E], vec![1, 2, 0], vec![2, 1, E]], 4),
+			(Direction::Right, vec![vec![E, E, 1], vec![E, 1, 0], vec![1, 2, 0], vec![E, 2, 1]], 4),
+			(Direction::Up, vec![vec![0, 0, 1], vec![2, 3, 1], vec![E, E, E], vec![E, E, E]], 28),
+			(Direction::Down, vec![vec![E, E, E], vec![E, E, E], vec![0, 0, 1], vec![2, 3, 1]], 28)
+		];
+
+		for (direction, cells_after, score) in expected.iter() {
+			let mut board = board(cells.clone());
+			let outcome = board.slide(*direction);
+
+			assert_eq!(board.cells(), cells_after, "{:?}", direction);
+			assert_eq!(outcome.score, *score, "{:?}", direction);
+			assert_eq!(board.width(), 3);
+			assert_eq!(board.height(), 4);
+		}
+	}
+}
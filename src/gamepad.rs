@@ -0,0 +1,249 @@
+use piston::input::{Button, ControllerAxisEvent, Event, HatState, PressEvent};
+use std::collections::HashMap;
+
+use twenty::engine::Direction;
+
+use crate::bindings::Action;
+
+// Face buttons named by their position, so they mean the same on every controller
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FaceButton {
+	South,
+	East,
+	West,
+	North
+}
+
+impl FaceButton {
+	fn action(&self) -> Action {
+		match self {
+			FaceButton::South => Action::Continue,
+			FaceButton::East => Action::Undo,
+			FaceButton::West => Action::Redo,
+			FaceButton::North => Action::Reset
+		}
+	}
+}
+
+// An analog stick, or a D-pad reported as a pair of axes
+#[derive(Debug, Default)]
+struct Stick {
+	// Horizontal and vertical position from -1 to 1, positive is right and down
+	position: [f64; 2],
+	// Whether the stick has been back in the dead zone since it last moved the board. Works like a
+	// key that has to be released before it can be pressed again.
+	centered: bool
+}
+
+// Turns gamepad input into the same actions as the keyboard. The D-pad and left stick slide the
+// board, the face buttons keep playing (south), undo (east), redo (west) and start a new game
+// (north).
+//
+// Glutin, the default window backend, does not report gamepads. Backends that do, such as SDL2,
+// are handled through `event`, and with the `gamepad` feature gamepads are read directly with gilrs.
+pub struct Gamepad {
+	// How far a stick has to be pushed to count, from 0 to 1
+	dead_zone: f64,
+	// Sticks of every connected gamepad
+	sticks: HashMap<(usize, usize), Stick>,
+	actions: Vec<Action>,
+	#[cfg(feature = "gamepad")]
+	gilrs: Option<gilrs::Gilrs>
+}
+
+impl Gamepad {
+	pub fn new(dead_zone: f64) -> Self {
+		#[cfg(not(feature = "gamepad"))]
+		println!("Gamepads are only read in builds with the gamepad feature, see the README");
+
+		Gamepad {
+			dead_zone,
+			sticks: HashMap::new(),
+			actions: Vec::new(),
+			#[cfg(feature = "gamepad")]
+			gilrs: gilrs::Gilrs::new()
+				.map_err(|err| println!("Gamepads are not available: {}", err))
+				.ok()
+		}
+	}
+
+	// Handle controller events from the window. Button numbers follow the Xbox layout used by SDL.
+	pub fn event(&mut self, e: &Event) {
+		if let Some(args) = e.controller_axis_args() {
+			// Only the left stick slides the board
+			if args.axis < 2 {
+				self.axis((args.id as usize, 0), args.axis as usize, args.position);
+			}
+		}
+
+		match e.press_args() {
+			Some(Button::Hat(hat)) => {
+				let direction = match hat.state {
+					HatState::Left => Some(Direction::Left),
+					HatState::Right => Some(Direction::Right),
+					HatState::Up => Some(Direction::Up),
+					HatState::Down => Some(Direction::Down),
+					_ => None
+				};
+
+				if let Some(direction) = direction {
+					self.actions.push(Action::slide(direction));
+				}
+			},
+			Some(Button::Controller(button)) => {
+				let face = match button.button {
+					0 => Some(FaceButton::South),
+					1 => Some(FaceButton::East),
+					2 => Some(FaceButton::West),
+					3 => Some(FaceButton::North),
+					_ => None
+				};
+
+				if let Some(face) = face {
+					self.actions.push(face.action());
+				}
+			},
+			_ => ()
+		}
+	}
+
+	// Read the events of gamepads connected through gilrs
+	#[cfg(feature = "gamepad")]
+	pub fn poll(&mut self) {
+		use gilrs::{Axis, Button, EventType};
+
+		let mut events = Vec::new();
+
+		if let Some(gilrs) = &mut self.gilrs {
+			while let Some(event) = gilrs.next_event() {
+				events.push((usize::from(event.id), event.event));
+			}
+		}
+
+		for (id, event) in events {
+			match event {
+				EventType::ButtonPressed(button, _) => {
+					let action = match button {
+						Button::DPadLeft => Some(Action::slide(Direction::Left)),
+						Button::DPadRight => Some(Action::slide(Direction::Right)),
+						Button::DPadUp => Some(Action::slide(Direction::Up)),
+						Button::DPadDown => Some(Action::slide(Direction::Down)),
+						Button::South => Some(FaceButton::South.action()),
+						Button::East => Some(FaceButton::East.action()),
+						Button::West => Some(FaceButton::West.action()),
+						Button::North => Some(FaceButton::North.action()),
+						_ => None
+					};
+
+					if let Some(action) = action {
+						self.actions.push(action);
+					}
+				},
+				// Gilrs points the vertical axes up instead of down
+				EventType::AxisChanged(Axis::LeftStickX, value, _) => self.axis((id, 0), 0, value as f64),
+				EventType::AxisChanged(Axis::LeftStickY, value, _) => self.axis((id, 0), 1, -value as f64),
+				EventType::AxisChanged(Axis::DPadX, value, _) => self.axis((id, 1), 0, value as f64),
+				EventType::AxisChanged(Axis::DPadY, value, _) => self.axis((id, 1), 1, -value as f64),
+				EventType::Disconnected => self.sticks.retain(|&(stick_id, _), _| stick_id != id),
+				_ => ()
+			}
+		}
+	}
+
+	#[cfg(not(feature = "gamepad"))]
+	pub fn poll(&mut self) {}
+
+	// Actions since the last call
+	pub fn take_actions(&mut self) -> Vec<Action> {
+		std::mem::take(&mut self.actions)
+	}
+
+	// Move one axis of a stick, sliding the board once each time it is pushed out of the dead zone
+	fn axis(&mut self, stick: (usize, usize), axis: usize, position: f64) {
+		let dead_zone = self.dead_zone;
+		let stick = self.sticks.entry(stick).or_insert_with(|| Stick { centered: true, ..Stick::default() });
+		stick.position[axis] = position;
+
+		let [x, y] = stick.position;
+
+		if x.abs().max(y.abs()) < dead_zone {
+			stick.centered = true;
+			return;
+		}
+
+		if !stick.centered { return; }
+		stick.centered = false;
+
+		// Slide along whichever axis is pushed furthest
+		let direction =
+			if x.abs() >= y.abs() { if x < 0.0 { Direction::Left } else { Direction::Right } }
+			else if y < 0.0 { Direction::Up }
+			else { Direction::Down };
+
+		self.actions.push(Action::slide(direction));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const LEFT_STICK: (usize, usize) = (0, 0);
+
+	#[test]
+	fn pushes_inside_the_dead_zone_are_ignored() {
+		let mut gamepad = Gamepad::new(0.5);
+
+		gamepad.axis(LEFT_STICK, 0, 0.3);
+		gamepad.axis(LEFT_STICK, 1, -0.49);
+
+		assert!(gamepad.take_actions().is_empty());
+	}
+
+	#[test]
+	fn slides_along_the_axis_pushed_furthest() {
+		let mut gamepad = Gamepad::new(0.5);
+		let pushes = [
+			([-0.9, 0.2], Direction::Left),
+			([0.8, -0.6], Direction::Right),
+			([0.3, -0.7], Direction::Up),
+			([-0.4, 0.9], Direction::Down)
+		];
+
+		for &([x, y], direction) in pushes.iter() {
+			gamepad.axis(LEFT_STICK, 0, x);
+			gamepad.axis(LEFT_STICK, 1, y);
+			assert_eq!(gamepad.take_actions(), vec![Action::slide(direction)], "push to {:?}", [x, y]);
+
+			// Back to the center before the next push
+			gamepad.axis(LEFT_STICK, 0, 0.0);
+			gamepad.axis(LEFT_STICK, 1, 0.0);
+		}
+	}
+
+	#[test]
+	fn slides_once_per_push() {
+		let mut gamepad = Gamepad::new(0.5);
+
+		gamepad.axis(LEFT_STICK, 0, 0.6);
+		gamepad.axis(LEFT_STICK, 0, 1.0);
+		gamepad.axis(LEFT_STICK, 1, 0.9);
+		assert_eq!(gamepad.take_actions(), vec![Action::MoveRight]);
+
+		// Leaving the dead zone again slides again
+		gamepad.axis(LEFT_STICK, 0, 0.1);
+		gamepad.axis(LEFT_STICK, 1, 0.1);
+		gamepad.axis(LEFT_STICK, 0, 0.7);
+		assert_eq!(gamepad.take_actions(), vec![Action::MoveRight]);
+	}
+
+	#[test]
+	fn sticks_are_separate() {
+		let mut gamepad = Gamepad::new(0.5);
+
+		gamepad.axis(LEFT_STICK, 0, 1.0);
+		gamepad.axis((1, 0), 1, 1.0);
+
+		assert_eq!(gamepad.take_actions(), vec![Action::MoveRight, Action::MoveDown]);
+	}
+}
@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+
+use crate::engine::Board;
+
+// Bounded undo/redo stack of board snapshots. Each snapshot holds the cells, score and generator
+// state, so undoing a move also takes back the tile it spawned and redoing it spawns the same tile.
+#[derive(Debug, Clone)]
+pub struct History {
+	undo: VecDeque<Board>,
+	redo: Vec<Board>,
+	depth: usize
+}
+
+impl History {
+	// Create a history that keeps at most `depth` moves. A depth of 0 disables undo.
+	pub fn new(depth: usize) -> Self {
+		History {
+			undo: VecDeque::with_capacity(depth),
+			redo: Vec::with_capacity(depth),
+			depth
+		}
+	}
+
+	pub fn is_enabled(&self) -> bool {
+		self.depth > 0
+	}
+
+	pub fn can_undo(&self) -> bool {
+		!self.undo.is_empty()
+	}
+
+	pub fn can_redo(&self) -> bool {
+		!self.redo.is_empty()
+	}
+
+	// Record the board as it was before a move. Making a new move discards everything that could
+	// have been redone.
+	pub fn record(&mut self, before: Board) {
+		if !self.is_enabled() {
+			return;
+		}
+
+		self.redo.clear();
+		self.undo.push_back(before);
+
+		// Forget the oldest moves once the history is full
+		while self.undo.len() > self.depth {
+			self.undo.pop_front();
+		}
+	}
+
+	// Returns the board before the last move, storing `current` so it can be redone
+	pub fn undo(&mut self, current: Board) -> Option<Board> {
+		let previous = self.undo.pop_back()?;
+		self.redo.push(current);
+
+		Some(previous)
+	}
+
+	// Returns the board after the last undone move, storing `current` so it can be undone again
+	pub fn redo(&mut self, current: Board) -> Option<Board> {
+		let next = self.redo.pop()?;
+		self.undo.push_back(current);
+
+		Some(next)
+	}
+
+	pub fn clear(&mut self) {
+		self.undo.clear();
+		self.redo.clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{engine::EMPTY, rng::GameRng};
+
+	// Boards are told apart by their score
+	fn board(score: u32) -> Board {
+		Board::with_state(vec![vec![EMPTY; 4]; 4], score, 0, 0, GameRng::from_seed(0))
+	}
+
+	fn score(board: Option<Board>) -> Option<u32> {
+		board.map(|board| board.score())
+	}
+
+	#[test]
+	fn undo_and_redo_in_order() {
+		let mut history = History::new(8);
+		history.record(board(0));
+		history.record(board(1));
+
+		assert_eq!(score(history.undo(board(2))), Some(1));
+		assert_eq!(score(history.undo(board(1))), Some(0));
+		assert_eq!(score(history.undo(board(0))), None);
+
+		assert_eq!(score(history.redo(board(0))), Some(1));
+		assert_eq!(score(history.redo(board(1))), Some(2));
+		assert_eq!(score(history.redo(board(2))), None);
+	}
+
+	#[test]
+	fn keeps_at_most_depth_moves() {
+		let mut history = History::new(2);
+
+		for i in 0..5 {
+			history.record(board(i));
+		}
+
+		// Only the last two moves can be undone
+		assert_eq!(score(history.undo(board(5))), Some(4));
+		assert_eq!(score(history.undo(board(4))), Some(3));
+		assert!(!history.can_undo());
+	}
+
+	#[test]
+	fn depth_0_disables_undo() {
+		let mut history = History::new(0);
+		history.record(board(0));
+
+		assert!(!history.is_enabled());
+		assert!(!history.can_undo());
+		assert_eq!(score(history.undo(board(1))), None);
+	}
+
+	#[test]
+	fn new_move_clears_redo() {
+		let mut history = History::new(8);
+		history.record(board(0));
+		history.record(board(1));
+		history.undo(board(2));
+
+		assert!(history.can_redo());
+
+		history.record(board(1));
+
+		assert!(!history.can_redo());
+		assert_eq!(score(history.redo(board(3))), None);
+		assert_eq!(score(history.undo(board(3))), Some(1));
+	}
+
+	#[test]
+	fn clear_forgets_everything() {
+		let mut history = History::new(8);
+		history.record(board(0));
+		history.record(board(1));
+		history.undo(board(2));
+		history.clear();
+
+		assert!(!history.can_undo());
+		assert!(!history.can_redo());
+	}
+}
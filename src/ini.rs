@@ -0,0 +1,243 @@
+use std::{fmt, fs, io, path::Path};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Line {
+	Section(String),
+	Entry { key: String, value: String },
+	// Blank lines, comments and anything that could not be parsed, kept as-is
+	Other(String)
+}
+
+// A simple INI document. Lines are kept in their original order so that unknown sections, keys
+// and comments survive a load and save round trip.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Ini {
+	lines: Vec<Line>
+}
+
+impl Ini {
+	pub fn new() -> Self {
+		Ini::default()
+	}
+
+	// Parse a document. Parsing never fails, malformed lines are kept but otherwise ignored.
+	pub fn parse(input: &str) -> Self {
+		let lines = input
+			.lines()
+			.map(|line| {
+				let trimmed = line.trim();
+
+				if trimmed.starts_with('[') && trimmed.ends_with(']') {
+					Line::Section(trimmed[1..trimmed.len() - 1].trim().to_string())
+				}
+				else if trimmed.starts_with(';') || trimmed.starts_with('#') {
+					Line::Other(line.to_string())
+				}
+				else if let Some(index) = trimmed.find('=') {
+					Line::Entry {
+						key: trimmed[..index].trim().to_string(),
+						value: trimmed[index + 1..].trim().to_string()
+					}
+				}
+				else {
+					Line::Other(line.to_string())
+				}
+			})
+			.collect();
+
+		Ini { lines }
+	}
+
+	// Load a document from disk. A missing or unreadable file gives an empty document.
+	pub fn load<P: AsRef<Path>>(path: P) -> Self {
+		match fs::read(path) {
+			Ok(bytes) => Ini::parse(&String::from_utf8_lossy(&bytes)),
+			Err(_) => Ini::new()
+		}
+	}
+
+	// Write the document to disk. The file is replaced in one step so a failed write does not leave
+	// a half written file behind.
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+		let path = path.as_ref();
+		let temp_path = path.with_extension("tmp");
+
+		fs::write(&temp_path, self.to_string())?;
+		fs::rename(&temp_path, path)
+	}
+
+	// Index range of the lines belonging to a section, excluding the header. Keys before the first
+	// header belong to the section named "".
+	fn section_range(&self, section: &str) -> Option<(usize, usize)> {
+		let start = if section.is_empty() {
+			0
+		}
+		else {
+			self.lines.iter().position(|line| matches!(line, Line::Section(name) if name == section))? + 1
+		};
+
+		let end = self.lines[start..]
+			.iter()
+			.position(|line| matches!(line, Line::Section(_)))
+			.map(|index| start + index)
+			.unwrap_or_else(|| self.lines.len());
+
+		Some((start, end))
+	}
+
+	pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+		let (start, end) = self.section_range(section)?;
+
+		self.lines[start..end].iter().find_map(|line| match line {
+			Line::Entry { key: k, value } if k == key => Some(value.as_str()),
+			_ => None
+		})
+	}
+
+	// Parse a value, returning `None` if it is missing or invalid
+	pub fn get_parsed<T: std::str::FromStr>(&self, section: &str, key: &str) -> Option<T> {
+		self.get(section, key).and_then(|value| value.parse().ok())
+	}
+
+	// Set a value, adding the key and section if they do not exist yet
+	pub fn set<V: ToString>(&mut self, section: &str, key: &str, value: V) {
+		let value = value.to_string();
+
+		match self.section_range(section) {
+			Some((start, end)) => {
+				// Replace an existing entry
+				for line in &mut self.lines[start..end] {
+					if let Line::Entry { key: k, value: v } = line {
+						if k == key {
+							*v = value;
+							return;
+						}
+					}
+				}
+
+				// Insert after the last entry of the section, before any trailing blank lines
+				let index = self.lines[start..end]
+					.iter()
+					.rposition(|line| matches!(line, Line::Entry { .. }))
+					.map(|index| start + index + 1)
+					.unwrap_or(end);
+
+				self.lines.insert(index, Line::Entry { key: key.to_string(), value });
+			},
+			None => {
+				self.lines.push(Line::Section(section.to_string()));
+				self.lines.push(Line::Entry { key: key.to_string(), value });
+			}
+		}
+	}
+}
+
+impl fmt::Display for Ini {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for line in &self.lines {
+			match line {
+				Line::Section(name) => writeln!(f, "[{}]", name)?,
+				Line::Entry { key, value } => writeln!(f, "{} = {}", key, value)?,
+				Line::Other(text) => writeln!(f, "{}", text)?
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{path::PathBuf, process};
+
+	const DOCUMENT: &str = "; written by the game\nglobal = 1\n\n[Settings]\n# speed of the animations\nanimation_speed = 1\nfuture_setting = on\n\n[Plugin]\nname = something\nnot an entry\n";
+
+	// A file in the temporary directory that is removed when the test is done
+	struct TempFile(PathBuf);
+
+	impl TempFile {
+		fn new(name: &str) -> Self {
+			TempFile(std::env::temp_dir().join(format!("twenty-ini-{}-{}", process::id(), name)))
+		}
+	}
+
+	impl Drop for TempFile {
+		fn drop(&mut self) {
+			let _ = fs::remove_file(&self.0);
+		}
+	}
+
+	#[test]
+	fn unknown_lines_survive_a_round_trip() {
+		let mut ini = Ini::parse(DOCUMENT);
+		ini.set("Settings", "animation_speed", 2);
+
+		let written = ini.to_string();
+
+		assert_eq!(written, DOCUMENT.replace("animation_speed = 1", "animation_speed = 2"));
+		assert_eq!(Ini::parse(&written), ini);
+	}
+
+	#[test]
+	fn reads_values_by_section() {
+		let ini = Ini::parse(DOCUMENT);
+
+		assert_eq!(ini.get("", "global"), Some("1"));
+		assert_eq!(ini.get("Settings", "future_setting"), Some("on"));
+		assert_eq!(ini.get("Plugin", "name"), Some("something"));
+		assert_eq!(ini.get("Settings", "name"), None);
+		assert_eq!(ini.get("Missing", "name"), None);
+	}
+
+	#[test]
+	fn set_adds_keys_and_sections() {
+		let mut ini = Ini::parse(DOCUMENT);
+		ini.set("Settings", "ranked", true);
+		ini.set("Statistics", "wins", 3);
+
+		// New keys go after the last entry of their section, new sections at the end
+		assert_eq!(
+			ini.to_string(),
+			DOCUMENT.replace("future_setting = on\n", "future_setting = on\nranked = true\n") + "[Statistics]\nwins = 3\n"
+		);
+	}
+
+	#[test]
+	fn invalid_values_are_not_parsed() {
+		let ini = Ini::parse("[Settings]\nundo_depth = lots\nanimation_speed = 1.5\n");
+
+		assert_eq!(ini.get_parsed::<usize>("Settings", "undo_depth"), None);
+		assert_eq!(ini.get_parsed::<f64>("Settings", "animation_speed"), Some(1.5));
+		assert_eq!(ini.get_parsed::<usize>("Settings", "animation_speed"), None);
+	}
+
+	#[test]
+	fn missing_file_is_empty() {
+		let file = TempFile::new("missing.ini");
+
+		assert_eq!(Ini::load(&file.0), Ini::new());
+	}
+
+	#[test]
+	fn corrupt_file_keeps_what_it_can() {
+		let file = TempFile::new("corrupt.ini");
+		fs::write(&file.0, b"[Settings]\nundo_depth = 5\n\xff\xfe garbage\n[Broken\n").unwrap();
+
+		let ini = Ini::load(&file.0);
+
+		assert_eq!(ini.get_parsed::<usize>("Settings", "undo_depth"), Some(5));
+		assert_eq!(ini.to_string(), "[Settings]\nundo_depth = 5\n\u{fffd}\u{fffd} garbage\n[Broken\n");
+	}
+
+	#[test]
+	fn save_and_load() {
+		let file = TempFile::new("saved.ini");
+		let ini = Ini::parse(DOCUMENT);
+
+		ini.save(&file.0).unwrap();
+
+		assert_eq!(Ini::load(&file.0), ini);
+		assert!(!file.0.with_extension("tmp").exists());
+	}
+}
@@ -0,0 +1,13 @@
+// Game rules shared by every frontend. Nothing in here depends on piston, so bots, tests and tools
+// can drive a game without opening a window.
+pub mod engine;
+pub mod ini;
+pub mod rng;
+pub mod save;
+pub mod history;
+pub mod spawn;
+pub mod ai;
+pub mod bitboard;
+pub mod strategy;
+pub mod simulation;
+pub mod replay;
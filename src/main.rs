@@ -1,25 +1,73 @@
 use opengl_graphics::OpenGL;
 use piston_window::{WindowSettings, PistonWindow};
 
+mod assets;
 mod tile;
 mod playboard;
 mod utils;
 mod app;
+mod user_data;
+mod options;
+mod tui;
+mod simulate;
+mod viewer;
+mod bot;
+mod bindings;
+mod controls;
+mod gamepad;
+mod swipe;
+mod theme;
 
 use app::App;
+use options::Options;
 
 fn main() {
+	// Simulate games without opening a window if asked to
+	if simulate::requested() {
+		simulate::run(std::env::args().skip(2));
+		return;
+	}
+
+	let options = Options::from_args();
+
+	// Choose where assets and user data are loaded from before anything is loaded
+	if let Err(err) = assets::init(options.assets.as_deref()) {
+		println!("{}", err);
+		std::process::exit(2);
+	}
+
+	// Play in the terminal if asked to
+	if options.tui {
+		if let Err(err) = tui::run(options) {
+			println!("Terminal error: {}", err);
+		}
+
+		return;
+	}
+
+	// Load the replay to watch before opening the window, so a bad file fails right away
+	let replay = match &options.replay {
+		Some(path) => match user_data::load_replay(path) {
+			Ok(replay) => Some(replay),
+			Err(err) => {
+				println!("Could not load replay: {}", err);
+				std::process::exit(1);
+			}
+		},
+		None => None
+	};
+
 	// Create a window
 	let window_res: Result<PistonWindow, Box<_>> = WindowSettings::new("2048 game", [700, 500])
 		.graphics_api(OpenGL::V3_2)
-		.exit_on_esc(true)
-		.resizable(false)
+		.exit_on_esc(false)
+		.resizable(true)
 		.samples(0)
 		.build();
 
 	if let Ok(window) = window_res {
 		// Create a new app
-		let mut app = App::new(window);
+		let mut app = App::new(window, options, replay);
 
 		// Begin app
 		app.init();
@@ -0,0 +1,97 @@
+use std::{env, path::PathBuf};
+
+use crate::playboard::PlayBoard;
+
+const USAGE: &str = "Usage: twenty [--seed <number>] [--size <size>] [--tui] [--replay <path>] [--assets <path>]
+       twenty simulate [options], see twenty simulate --help
+
+Options:
+	--seed <number>    Play with a fixed seed so the same tiles spawn every game
+	--size <size>      Start a new game on a board of this size, either a single number from 3 to 8
+	                   for a square board or width x height such as 5x3
+	--tui              Play in the terminal instead of opening a window
+	--replay <path>    Watch a recorded game, finished games are recorded in assets/replays
+	--assets <path>    Directory with the fonts, images, themes and user data. By default the
+	                   TWENTY_ASSETS environment variable, assets next to the executable or assets
+	                   in the working directory, whichever exists first";
+
+// Command line options
+#[derive(Debug, Default)]
+pub struct Options {
+	pub seed: Option<u64>,
+	pub size: Option<(usize, usize)>,
+	pub tui: bool,
+	pub replay: Option<PathBuf>,
+	pub assets: Option<PathBuf>
+}
+
+impl Options {
+	// Parse the process arguments, exiting with a usage message if they are invalid
+	pub fn from_args() -> Self {
+		match Options::parse(env::args().skip(1)) {
+			Ok(options) => options,
+			Err(err) => {
+				println!("{}\n\n{}", err, USAGE);
+				std::process::exit(2);
+			}
+		}
+	}
+
+	pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+		let mut options = Options::default();
+
+		while let Some(arg) = args.next() {
+			match arg.as_str() {
+				"--seed" => {
+					let value = args.next().ok_or("Missing value for --seed")?;
+
+					options.seed = Some(value.parse().map_err(|_| format!("Invalid seed `{}`", value))?);
+				},
+				"--size" => {
+					let value = args.next().ok_or("Missing value for --size")?;
+
+					options.size = Some(parse_board_size(&value).ok_or(format!(
+						"Invalid board size `{}`, expected sizes from {} to {} such as 4 or 5x3",
+						value,
+						PlayBoard::MIN_SIZE,
+						PlayBoard::MAX_SIZE
+					))?);
+				},
+				"--tui" => {
+					options.tui = true;
+				},
+				"--replay" => {
+					options.replay = Some(PathBuf::from(args.next().ok_or("Missing value for --replay")?));
+				},
+				"--assets" => {
+					options.assets = Some(PathBuf::from(args.next().ok_or("Missing value for --assets")?));
+				},
+				"--help" | "-h" => {
+					println!("{}", USAGE);
+					std::process::exit(0);
+				},
+				_ => return Err(format!("Unknown argument `{}`", arg))
+			}
+		}
+
+		Ok(options)
+	}
+}
+
+// Parse a board size as either `4` for a square board or `5x3` for width and height
+pub fn parse_board_size(input: &str) -> Option<(usize, usize)> {
+	let valid = |size: &usize| (PlayBoard::MIN_SIZE..=PlayBoard::MAX_SIZE).contains(size);
+	let mut parts = input.split('x');
+
+	let width = parts.next()?.trim().parse().ok().filter(valid)?;
+	let height = match parts.next() {
+		Some(height) => height.trim().parse().ok().filter(valid)?,
+		None => width
+	};
+
+	if parts.next().is_some() {
+		return None;
+	}
+
+	Some((width, height))
+}
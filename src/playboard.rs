@@ -1,337 +1,653 @@
 use piston::input::UpdateArgs;
 use piston_window::{Context, G2d};
-use rand::{thread_rng, Rng};
 use graphics::*;
-use std::cmp;
+use graphics::character::CharacterCache;
+use std::{cmp, collections::VecDeque};
+
+use twenty::{
+	engine::{self, Board, Direction, MoveOutcome, Spawn},
+	history::History,
+	replay::{Replay, ReplayStep}
+};
 
 use crate::{
-	utils::{Vector, RenderContext, Direction, Rgba, multi_line_text},
-	tile::{Tile, MovingTile}
+	bindings::{Action, Bindings},
+	utils::{RenderContext, LAYOUT_SIZE, mix, multi_line_text, scaled_text},
+	tile::{Tile, MovingTile, SLIDE_TIME}
 };
 
-const TEXT_COLOR: Rgba = [0.463, 0.431, 0.400, 1.0];
-const TR_WHITE: Rgba = [1.0, 1.0, 1.0, 0.5];
+// Seconds a "+N" label takes to rise and fade, the score takes to count up to a new value and the
+// high score flashes for once it is beaten. All at normal animation speed.
+const POPUP_TIME: f64 = 0.8;
+const COUNT_TIME: f64 = 0.25;
+const FLASH_TIME: f64 = 1.2;
+
+// How far a "+N" label rises and how many times the high score flashes
+const POPUP_RISE: f64 = 24.0;
+const FLASHES: f64 = 3.0;
+
+// Position of the score text in the layout and the gap between the score and a "+N" label
+const SCORE_POSITION: [f64; 2] = [245.0, 28.0];
+const POPUP_GAP: f64 = 8.0;
+
+// Points scored by a move, shown rising from the score
+struct ScorePopup {
+	points: u32,
+	// Seconds since the move at normal animation speed
+	age: f64
+}
 
 #[derive(PartialEq)]
 enum BoardState {
 	Idle,
 	Moving,
+	Won,
+	GameOver
+}
+
+// Things that happened to the game, collected by `App` for statistics
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoardEvent {
+	Won,
+	Continued,
 	GameOver
 }
 
+// Animated view over a `Board`. The board is always up to date, the tiles here only catch up with it
+// once the moving tiles have finished animating.
 pub struct PlayBoard {
+	board: Board,
 	tiles: Vec<Vec<Tile>>,
 	moving_tiles: Vec<MovingTile>,
-	board_size: usize,
+	pending_spawn: Option<Spawn>,
+	// Moves made while tiles were still moving, played once the board is idle
+	queue: VecDeque<Direction>,
+	queue_length: usize,
+	// Jump to the end of the running animation when a new move arrives instead of queueing it
+	finish_on_input: bool,
+	// Multiplier of the animation speed, 0 skips animations
+	animation_speed: f64,
+	history: History,
+	replay: Replay,
 	state: BoardState,
-	score: u32,
-	highscore: u32
+	events: Vec<BoardEvent>,
+	highscore: u32,
+	// Whether the board plays back a replay, which never changes the high score
+	playback: bool,
+	score_popups: Vec<ScorePopup>,
+	// Score shown while counting up to the board's score, and the points per second it counts at
+	shown_score: f64,
+	count_rate: f64,
+	// Seconds since the high score was beaten, while it is flashing
+	highscore_flash: Option<f64>,
+	// Whether the high score has flashed this game, it only flashes the first time it is beaten
+	highscore_beaten: bool,
+	// Whether the win has been counted this game, undoing the winning move and making it again
+	// doesn't count it twice
+	win_counted: bool,
+	// Names of the keys shown on the win and game over overlays
+	continue_key: String,
+	reset_key: String
 }
 
 impl PlayBoard {
 	pub const PADDED_TILE_SIZE: f64 = 110.0;
 
-	pub fn new(board_size: usize) -> Self {
-		let mut tiles = Vec::with_capacity(board_size);
+	// Smallest and largest boards that can be played
+	pub const MIN_SIZE: usize = engine::MIN_SIZE;
+	pub const MAX_SIZE: usize = engine::MAX_SIZE;
+
+	// Create a view over an existing board, such as one loaded from a save file
+	pub fn with_board(board: Board) -> Self {
+		let (width, height) = (board.width(), board.height());
+		let mut tiles = Vec::with_capacity(height);
 
 		// Fill board with empty tiles
-		for y in 0..board_size {
-			tiles.push(Vec::with_capacity(board_size));
+		for y in 0..height {
+			tiles.push(Vec::with_capacity(width));
 
-			for x in 0..board_size {
+			for x in 0..width {
 				tiles[y].push(Tile::empty(x as f64, y as f64));
 			}
 		}
 
-		PlayBoard {
-			tiles: tiles,
-			moving_tiles: Vec::with_capacity(board_size.pow(2)),
-			board_size: board_size,
+		let mut play_board = PlayBoard {
+			replay: Replay::new(&board),
+			shown_score: board.score() as f64,
+			board,
+			tiles,
+			moving_tiles: Vec::with_capacity(width * height),
+			pending_spawn: None,
+			queue: VecDeque::new(),
+			queue_length: 0,
+			finish_on_input: false,
+			animation_speed: 1.0,
+			history: History::new(0),
 			state: BoardState::Idle,
-			score: 0,
-			highscore: 0
-		}
+			events: Vec::new(),
+			highscore: 0,
+			playback: false,
+			score_popups: Vec::new(),
+			count_rate: 0.0,
+			highscore_flash: None,
+			highscore_beaten: false,
+			win_counted: false,
+			continue_key: String::new(),
+			reset_key: String::new()
+		};
+
+		play_board.set_prompt_keys(&Bindings::default());
+
+		play_board.sync_tiles();
+		play_board
 	}
 
-	fn get_empty_tile(&self) -> Option<Vector> {
-		// Create a result vector with a max capacity of board size ^ 2
-		let mut res: Vec<Vector> = Vec::with_capacity(self.tiles.len().pow(2));
+	// Set how many moves can be undone, 0 disables undo. Clears the current history.
+	pub fn set_undo_depth(&mut self, depth: usize) {
+		self.history = History::new(depth);
+	}
 
-		// Search for empty tiles
-		for y in 0..self.board_size {
-			for x in 0..self.board_size {
-				if self.tiles[y][x].value == 99 {
-					res.push(Vector::new(x as f64, y as f64));
-				}
+	pub fn undo_enabled(&self) -> bool {
+		self.history.is_enabled()
+	}
+
+	// Set how many moves made during an animation are kept, 0 drops them
+	pub fn set_queue_length(&mut self, length: usize) {
+		self.queue_length = length;
+		self.queue.truncate(length);
+	}
+
+	pub fn set_finish_on_input(&mut self, finish: bool) {
+		self.finish_on_input = finish;
+	}
+
+	pub fn set_animation_speed(&mut self, speed: f64) {
+		self.animation_speed = speed;
+	}
+
+	pub fn animation_speed(&self) -> f64 {
+		self.animation_speed
+	}
+
+	fn animated(&self) -> bool {
+		self.animation_speed > 0.0
+	}
+
+	// Show the board's tiles as they are, dropping any running animation
+	fn sync_tiles(&mut self) {
+		self.moving_tiles.clear();
+		self.pending_spawn = None;
+		self.queue.clear();
+		self.shown_score = self.board.score() as f64;
+
+		for (y, row) in self.board.cells().iter().enumerate() {
+			for (x, &value) in row.iter().enumerate() {
+				self.tiles[y][x].value = value;
+				self.tiles[y][x].stop_animation();
 			}
 		}
 
-		// Returns position of random empty tile (if it exists)
-		match res.len() {
-			0 => None,
-			_ => Some(res.remove((rand::random::<f32>() * (res.len() - 1) as f32).round() as usize))
-		}
+		self.state =
+			if self.board.has_won() { BoardState::Won }
+			else if self.board.is_game_over() { BoardState::GameOver }
+			else { BoardState::Idle };
 	}
 
-	// Spawn a tile at a random location
-	pub fn spawn_tile(&mut self) {
-		match self.get_empty_tile() {
-			Some(pos) => {
-				let mut rng = thread_rng();
-				self.tiles[pos.y as usize][pos.x as usize].value = rng.gen_range(0, 2);
-				self.tiles[pos.y as usize][pos.x as usize].scale = 1.1;
-			},
-			_ => ()
+	fn show_spawn(&mut self, spawn: Spawn) {
+		let animated = self.animated();
+		let tile = &mut self.tiles[spawn.y][spawn.x];
+
+		tile.value = spawn.value;
+
+		if animated {
+			tile.grow();
 		}
 	}
 
-	fn spawn_moving_tile(&mut self, x: isize, y: isize, direction: &Direction, walker: isize, combine: bool) {
-		let tiles = &mut self.tiles;
-
-		// Get destination position
-		let (dx, dy) = direction.displacement();
-		let (walker_x, walker_y) = (
-			(x + walker * dx) as usize,
-			(y + walker * dy) as usize);
-
-		// Get position as usize
-		let ux = x as usize;
-		let uy = y as usize;
-
-		// Spawn a moving tile
-		self.moving_tiles.push(MovingTile::new(
-			x as f64,
-			y as f64,
-			walker_x as f64,
-			walker_y as f64,
-			tiles[uy][ux].value,
-			combine
-		));
-		
-		// Set destination tile to `taken` if it will be combined with
-		if combine {
-			tiles[walker_y][walker_x].taken = true;
+	// Slide the board if it is waiting for a move, returns whether anything moved. Moves made while
+	// tiles are moving either finish the animation or are queued, depending on the settings.
+	pub fn slide(&mut self, direction: Direction) -> bool {
+		if self.state == BoardState::Moving {
+			if self.finish_on_input {
+				self.finish_animation();
+			}
+			else if self.queue.len() < self.queue_length {
+				self.queue.push_back(direction);
+				return false;
+			}
 		}
 
-		// Set destination tile's `occupied` value to the tile's value so that other moving tiles can combine with it 
-		tiles[walker_y][walker_x].occupied = tiles[uy][ux].value;
+		if self.state != BoardState::Idle { return false; }
 
-		// Reset current tile
-		tiles[uy][ux].value = 99;
-		tiles[uy][ux].occupied = 99;
-	}
+		let before = self.board.clone();
+		let outcome = self.board.play(direction);
 
-	pub fn slide(&mut self, direction: Direction) {
-		if self.state != BoardState::Idle { return; }
-
-		// Get vector displacement from direction
-		let (dx, dy) = direction.displacement();
+		if !outcome.moved() { return false; }
 
-		for y in 0..self.board_size {
-			for x in 0..self.board_size {
-				let tiles = &self.tiles;
+		self.history.record(before);
+		self.replay.record(direction, outcome.spawned);
 
-				// Get x and y position of tile
-				let ix =
-					if dx == 1 { (self.board_size - x - 1) as isize }
-					else { x as isize };
-				let iy =
-					if dy == 1 { (self.board_size - y - 1) as isize }
-					else { y as isize };
+		self.animate(outcome);
+		true
+	}
 
+	// Play a recorded move, placing its recorded tile instead of spawning a random one
+	pub fn replay_step(&mut self, step: &ReplayStep) {
+		if self.state != BoardState::Idle { return; }
 
-				// Skip if tile is empty
-				if tiles[iy as usize][ix as usize].value == 99 { continue; }
+		if let Some(outcome) = step.apply(&mut self.board) {
+			self.replay.record(step.direction, step.spawn);
+			self.animate(outcome);
+		}
+	}
 
-				// Makes iteration through columns go in the opposite direction
-				let mut walker: isize = 0;
-				let mut combine = false;
+	fn animate(&mut self, outcome: MoveOutcome) {
+		// Animate every tile that moved, clearing its old position
+		for tile_move in &outcome.moves {
+			let (x, y) = tile_move.from;
+			let (new_x, new_y) = tile_move.to;
+
+			self.moving_tiles.push(MovingTile::new(
+				x as f64,
+				y as f64,
+				new_x as f64,
+				new_y as f64,
+				tile_move.value,
+				tile_move.combine
+			));
+
+			self.tiles[y][x].reset();
+		}
 
-				// Find the max walking distance for the walker
-				let max_walk =
-					if direction.is_negative() {
-						cmp::max(ix * dx.abs(), iy * dy.abs())
-					}
-					else {
-						self.board_size as isize - cmp::max(ix * dx, iy * dy) - 1
-					};
-				
-				// Check if the tile to the left is occupied or not in a loop
-				while walker < max_walk {
-					walker += 1;
-
-					let (walker_x, walker_y) = (
-						(ix + walker * dx) as usize,
-						(iy + walker * dy) as usize);
-					
-					// Check if the tile is not empty
-					if tiles[walker_y][walker_x].value != 99 || tiles[walker_y][walker_x].occupied != 99 {
-						// If both tiles have the same value and the tile is not `taken`, enable the combine flag
-						if (tiles[walker_y][walker_x].value == tiles[iy as usize][ix as usize].value || 
-							tiles[walker_y][walker_x].occupied == tiles[iy as usize][ix as usize].value)
-						    && !tiles[walker_y][walker_x].taken {
-							combine = true;
-						}
-						// Move walker back 1 step
-						else {
-							walker -= 1;
-						}
-
-						// Break from loop if a tile is found
-						break;
-					}
-				}
+		// The spawned tile is shown once the moving tiles arrive
+		self.pending_spawn = outcome.spawned;
 
-				// Check if the tile can move to another location
-				if walker != 0 {
-					self.spawn_moving_tile(ix, iy, &direction, walker, combine);
-				}
-			}
+		if outcome.score > 0 {
+			self.show_points(outcome.score);
 		}
 
 		// Set board state to `moving` so that no more inputs are applied
 		self.state = BoardState::Moving;
+
+		// Without animations the tiles land right away
+		if !self.animated() {
+			for tile in &mut self.moving_tiles {
+				tile.update(SLIDE_TIME);
+			}
+
+			self.land_tiles();
+		}
 	}
 
-	fn game_over(&self) -> bool {
-		for y in 0..self.board_size {
-			for x in 0..self.board_size {
-				// Check for a match to the right
-				if x < self.board_size - 1 {
-					if self.tiles[y][x].value == self.tiles[y][x + 1].value {
-						return false;
-					}
-				}
+	// Show the points of a move rising from the score and count the score up to its new value. The
+	// high score flashes the first time the player beats it each game.
+	fn show_points(&mut self, points: u32) {
+		let score = self.board.score();
 
-				// Check for a match below
-				if y < self.board_size - 1 {
-					if self.tiles[y][x].value == self.tiles[y + 1][x].value {
-						return false;
-					}
-				}
+		if score > self.highscore && !self.playback {
+			// Nothing to celebrate before the first high score is set
+			if !self.highscore_beaten && self.highscore > 0 && self.animated() {
+				self.highscore_flash = Some(0.0);
 			}
+
+			self.highscore_beaten = true;
+			self.highscore = score;
 		}
 
-		true
+		if !self.animated() {
+			self.shown_score = score as f64;
+			return;
+		}
+
+		self.score_popups.push(ScorePopup { points, age: 0.0 });
+		self.count_rate = (score as f64 - self.shown_score) / COUNT_TIME;
 	}
 
 	pub fn render(&mut self, render_ctx: &mut RenderContext, ctx: Context, gl: &mut G2d) {
+		// Render the empty cells under everything
+		for tile in self.tiles.iter().flatten() {
+			tile.render_cell(render_ctx, ctx, gl);
+		}
+
+		// Tiles merging into another tile slide under it, so they disappear into it instead of covering it
+		for tile in self.moving_tiles.iter().filter(|tile| tile.combine) {
+			tile.render(render_ctx, ctx, gl);
+		}
+
 		// Render tiles
-		for row in &mut self.tiles {
-			for tile in row {
-				tile.render(render_ctx, ctx, gl);
-			}
+		for tile in self.tiles.iter().flatten() {
+			tile.render(render_ctx, ctx, gl);
 		}
 
-		// Render moving tiles
-		for tile in &mut self.moving_tiles {
+		// Render the other moving tiles
+		for tile in self.moving_tiles.iter().filter(|tile| !tile.combine) {
 			tile.render(render_ctx, ctx, gl);
 		}
 
+		// Text is positioned in the layout, which is scaled to the window
+		let layout = render_ctx.layout_transform(ctx);
+		let scale = render_ctx.layout_scale;
+		let (text_color, overlay) = (render_ctx.theme.text, render_ctx.theme.overlay);
+
 		// Get text transform
-		let transform = ctx.transform
-			.trans(245.0, 28.0);
+		let transform = layout
+			.trans(SCORE_POSITION[0], SCORE_POSITION[1]);
+
+		// Render current score, counting up after a move
+		let score = format!("Score: {}", self.shown_score.round());
+		scaled_text(text_color, 22, &score, &mut render_ctx.glyphs.text, transform, scale, gl);
+
+		// Render the points of the last moves rising from the score and fading out
+		let score_width = render_ctx.glyphs.text.width(22, &score).unwrap_or(0.0);
+		for popup in &self.score_popups {
+			let t = popup.age / POPUP_TIME;
+			let [r, g, b, a] = render_ctx.theme.accent;
+			let color = [r, g, b, a * (1.0 - t) as f32];
+			let transform = transform.trans(score_width + POPUP_GAP, -POPUP_RISE * t);
+
+			scaled_text(color, 22, &format!("+{}", popup.points), &mut render_ctx.glyphs.text, transform, scale, gl);
+		}
 
-		// Render current score
-		text(TEXT_COLOR, 22, &format!("Score: {:?}", self.score), &mut render_ctx.glyphs.brandon_blk, transform, gl)
-			.expect("Failed to draw text");
+		// Render high score, flashing when it is beaten
+		let highscore_color = match self.highscore_flash {
+			Some(age) => {
+				let flash = 0.5 - 0.5 * (age / FLASH_TIME * FLASHES * 2.0 * std::f64::consts::PI).cos();
+				mix(text_color, render_ctx.theme.accent, flash as f32)
+			},
+			None => text_color
+		};
+		scaled_text(highscore_color, 22, &format!("High score: {:?}", self.highscore), &mut render_ctx.glyphs.text, transform.trans(280.0, 0.0), scale, gl);
 
-		// Render high score
-		text(TEXT_COLOR, 22, &format!("High score: {:?}", self.highscore), &mut render_ctx.glyphs.brandon_blk, transform.trans(280.0, 0.0), gl)
-			.expect("Failed to draw text");
+		// Draw `Won` overlay and text
+		if self.state == BoardState::Won {
+			// Draw overlay
+			rectangle(overlay, [0.0, 0.0, render_ctx.window_size[0], render_ctx.window_size[1]], ctx.transform, gl);
+
+			// Get text transform
+			let transform = layout
+				.trans(LAYOUT_SIZE[0] / 2.0 - 65.0, 200.0);
+
+			// "YOU WIN" banner
+			scaled_text(text_color, 35, "You Win!", &mut render_ctx.glyphs.text, transform, scale, gl);
+
+			// Continue banner
+			multi_line_text(
+				text_color,
+				25,
+				&format!(
+					"              Score: {:?}\n\nKeep playing with {}\n Retry by pressing {}",
+					self.board.score(),
+					self.continue_key,
+					self.reset_key
+				),
+				&mut render_ctx.glyphs.text,
+				transform.trans(-50.0, 60.0),
+				scale,
+				gl
+			);
+		}
 
 		// Draw `GameOver` overlay and text
 		if self.state == BoardState::GameOver {
 			// Draw overlay
-			rectangle(TR_WHITE, [0.0, 0.0, render_ctx.window_size[0], render_ctx.window_size[1]], ctx.transform, gl);
+			rectangle(overlay, [0.0, 0.0, render_ctx.window_size[0], render_ctx.window_size[1]], ctx.transform, gl);
 
 			// Get text transform
-			let transform = ctx.transform
-				.trans(render_ctx.window_size[0] / 2.0 - 80.0, 200.0);
+			let transform = layout
+				.trans(LAYOUT_SIZE[0] / 2.0 - 80.0, 200.0);
 
 			// "GAME OVER" banner
-			text(TEXT_COLOR, 35, "Game Over!", &mut render_ctx.glyphs.brandon_blk, transform, gl)
-				.expect("Failed to draw text");
+			scaled_text(text_color, 35, "Game Over!", &mut render_ctx.glyphs.text, transform, scale, gl);
 
 			// Retry banner
 			multi_line_text(
-				TEXT_COLOR,
+				text_color,
 				25,
-				&format!("            Score: {:?}\n\nRetry by pressing SPACE", self.score),
-				&mut render_ctx.glyphs.brandon_blk,
+				&format!("            Score: {:?}\n\nRetry by pressing {}", self.board.score(), self.reset_key),
+				&mut render_ctx.glyphs.text,
 				transform.trans(-35.0, 60.0),
+				scale,
 				gl
 			);
 		}
 	}
 
-	pub fn update(&mut self, _args: &UpdateArgs) {
-		// Update all moving tiles
+	pub fn update(&mut self, args: &UpdateArgs) {
+		// Animations advance with the update tick so they run at the same speed at any frame rate
+		let dt = args.dt * self.animation_speed;
+
 		for tile in &mut self.moving_tiles {
+			tile.update(dt);
+		}
+
+		for tile in self.tiles.iter_mut().flatten() {
+			tile.update(dt);
+		}
+
+		// Count the score up, scores that went down after an undo or a new game are shown right away
+		let score = self.board.score() as f64;
+		self.shown_score = (self.shown_score + self.count_rate * dt).min(score);
+
+		for popup in &mut self.score_popups {
+			popup.age += dt;
+		}
+		self.score_popups.retain(|popup| popup.age < POPUP_TIME);
+
+		if let Some(age) = &mut self.highscore_flash {
+			*age += dt;
+
+			if *age >= FLASH_TIME {
+				self.highscore_flash = None;
+			}
+		}
+
+		self.land_tiles();
+	}
+
+	// Put the tiles that finished sliding in place, and end the move once every tile has
+	fn land_tiles(&mut self) {
+		let animated = self.animated();
+
+		for tile in &self.moving_tiles {
 			// Check if the moving tile is finished moving
 			if tile.is_finished() {
-				let destination = &mut self.tiles[tile.position.y as usize][tile.position.x as usize];
+				let destination = &mut self.tiles[tile.to.y as usize][tile.to.x as usize];
 
 				if tile.combine {
 					destination.value = tile.value + 1;
-					destination.scale = 1.2;
 
-					// Increment score
-					self.score += 2u32.pow(tile.value as u32 + 2);
+					if animated {
+						destination.pop();
+					}
 				}
 				else {
 					destination.value = tile.value;
 				}
-
-				destination.occupied = destination.value;
-				destination.taken = false;
 			}
 		}
 
-		let old_length = self.moving_tiles.len();
-
 		// Remove all moving tiles that have finished
 		self.moving_tiles.retain(|tile| !tile.is_finished());
 
 		// Set board state to idle if no more tiles are moving
-		let new_length = self.moving_tiles.len();
-		if new_length == 0 && self.state == BoardState::Moving {
+		if self.moving_tiles.is_empty() && self.state == BoardState::Moving {
 			self.state = BoardState::Idle;
 
-			// Spawn a new random tile if the tiles have moved
-			if old_length != new_length {
-				self.spawn_tile();
+			// Show the tile spawned by the move
+			if let Some(spawn) = self.pending_spawn.take() {
+				self.show_spawn(spawn);
 			}
 
-			// Check if the game is over
-			if let None = self.get_empty_tile() {
-				if self.game_over() {
-					self.state = BoardState::GameOver;
+			self.check_end();
 
-					// Set new highscore (if applicable)
-					self.highscore = cmp::max(self.score, self.highscore);
-				}
+			// Play the moves made while the tiles were moving
+			self.play_queue();
+		}
+	}
+
+	// Play queued moves until one of them moves the board
+	fn play_queue(&mut self) {
+		while let Some(direction) = self.queue.pop_front() {
+			if self.slide(direction) { break; }
+		}
+	}
+
+	// Jump to the end of the running animation
+	fn finish_animation(&mut self) {
+		if self.state != BoardState::Moving { return; }
+
+		// Showing the board as it is ends the animation, but the end of the game still has to be checked
+		self.sync_tiles();
+		self.state = BoardState::Idle;
+		self.check_end();
+	}
+
+	// Check if the game has been won or is over, queued moves are dropped if so
+	fn check_end(&mut self) {
+		if self.board.has_won() {
+			self.queue.clear();
+			self.state = BoardState::Won;
+
+			if !self.win_counted {
+				self.events.push(BoardEvent::Won);
+				self.win_counted = true;
 			}
 		}
+		else if self.board.is_game_over() {
+			self.queue.clear();
+			self.state = BoardState::GameOver;
+			self.events.push(BoardEvent::GameOver);
+
+			// Set new highscore (if applicable), the score of a replay is not the player's
+			if !self.playback {
+				self.highscore = cmp::max(self.board.score(), self.highscore);
+			}
+		}
+	}
+
+	// Keep playing after reaching the target tile
+	pub fn keep_playing(&mut self) {
+		if self.state != BoardState::Won { return; }
+
+		self.board.keep_playing();
+		self.events.push(BoardEvent::Continued);
+		self.state = BoardState::Idle;
+
+		// The winning move may also have been the last one
+		self.check_end();
 	}
 
-	pub fn reset(&mut self) {
+	// Events since the last call
+	pub fn take_events(&mut self) -> Vec<BoardEvent> {
+		std::mem::take(&mut self.events)
+	}
+
+	// Take back the last move
+	pub fn undo(&mut self) {
+		if self.state == BoardState::Moving || !self.history.can_undo() { return; }
+
+		if let Some(previous) = self.history.undo(self.board.clone()) {
+			self.board = previous;
+			self.replay.undo();
+			self.sync_tiles();
+		}
+	}
+
+	// Make the last undone move again
+	pub fn redo(&mut self) {
+		if self.state == BoardState::Moving || !self.history.can_redo() { return; }
+
+		if let Some(next) = self.history.redo(self.board.clone()) {
+			self.board = next;
+			self.replay.redo();
+			self.sync_tiles();
+		}
+	}
+
+	// Start a new game, with the given seed if there is one
+	pub fn reset(&mut self, seed: Option<u64>) {
+		let spawn = match seed {
+			Some(seed) => self.board.reset_with_seed(seed),
+			None => self.board.reset()
+		};
+		self.history.clear();
+		self.replay = Replay::new(&self.board);
+
+		// Drop any running animation and queued moves
+		self.moving_tiles.clear();
+		self.pending_spawn = None;
+		self.queue.clear();
+
 		// Set board to empty tiles
-		for y in 0..self.board_size {
-			for x in 0..self.board_size {
-				self.tiles[y][x].reset();
+		for row in &mut self.tiles {
+			for tile in row {
+				tile.reset();
 			}
 		}
 
-		// Reset score and state
-		self.score = 0;
+		// The high score can flash again in the new game
+		self.score_popups.clear();
+		self.shown_score = 0.0;
+		self.highscore_flash = None;
+		self.highscore_beaten = false;
+		self.win_counted = false;
+
+		// Reset state and show the spawned tile
 		self.state = BoardState::Idle;
 
-		// Spawn 1 random tile
-		self.spawn_tile();
+		if let Some(spawn) = spawn {
+			self.show_spawn(spawn);
+		}
+	}
+
+	pub fn board(&self) -> &Board {
+		&self.board
 	}
 
-	pub fn board_size(&self) -> usize {
-		self.board_size
+	// Recording of the current game
+	pub fn replay(&self) -> &Replay {
+		&self.replay
 	}
-}
\ No newline at end of file
+
+	// Continue an earlier recording, which must lead to the current board
+	pub fn set_replay(&mut self, replay: Replay) {
+		self.replay = replay;
+	}
+
+	pub fn is_game_over(&self) -> bool {
+		self.state == BoardState::GameOver
+	}
+
+	pub fn is_won(&self) -> bool {
+		self.state == BoardState::Won
+	}
+
+	// Whether the board is waiting for a move
+	pub fn is_idle(&self) -> bool {
+		self.state == BoardState::Idle
+	}
+
+	pub fn highscore(&self) -> u32 {
+		self.highscore
+	}
+
+	// Show the keys of the current bindings on the overlays
+	pub fn set_prompt_keys(&mut self, bindings: &Bindings) {
+		self.continue_key = bindings.first_key(Action::Continue);
+		self.reset_key = bindings.first_key(Action::Reset);
+	}
+
+	pub fn set_highscore(&mut self, highscore: u32) {
+		self.highscore = highscore;
+	}
+
+	// Mark the board as playing back a replay
+	pub fn set_playback(&mut self, playback: bool) {
+		self.playback = playback;
+	}
+
+	// Width and height of the board in tiles
+	pub fn board_size(&self) -> (usize, usize) {
+		(self.board.width(), self.board.height())
+	}
+}
@@ -0,0 +1,273 @@
+use crate::{
+	engine::{Board, Direction, MoveOutcome, Spawn, EMPTY, MAX_VALUE},
+	ini::Ini,
+	save::{self, SaveError}
+};
+
+// Version written to new replay files, separate from the version of the board stored in them
+pub const REPLAY_VERSION: u32 = 1;
+
+const REPLAY_SECTION: &str = "Replay";
+
+// A single move of a recorded game and the tile it spawned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayStep {
+	pub direction: Direction,
+	pub spawn: Option<Spawn>
+}
+
+impl ReplayStep {
+	// Play the move on a board, placing the recorded tile instead of spawning a random one. Returns
+	// `None` if the move does not fit the board, which leaves the board in an unspecified state.
+	pub fn apply(&self, board: &mut Board) -> Option<MoveOutcome> {
+		let mut outcome = board.slide(self.direction);
+
+		if !outcome.moved() {
+			return None;
+		}
+
+		if let Some(spawn) = self.spawn {
+			if spawn.x >= board.width() || spawn.y >= board.height() || board.get(spawn.x, spawn.y) != EMPTY {
+				return None;
+			}
+
+			board.set(spawn.x, spawn.y, spawn.value);
+		}
+
+		outcome.spawned = self.spawn;
+		Some(outcome)
+	}
+}
+
+// A game recorded move by move, starting from a snapshot of the board. Undone moves are dropped from
+// the recording, so it always leads to the board as it is being played.
+#[derive(Debug, Clone)]
+pub struct Replay {
+	initial: Board,
+	steps: Vec<ReplayStep>,
+	// Moves taken back by undo, most recent last
+	undone: Vec<ReplayStep>
+}
+
+impl Replay {
+	// Start recording from the given board
+	pub fn new(initial: &Board) -> Self {
+		Replay {
+			initial: initial.clone(),
+			steps: Vec::new(),
+			undone: Vec::new()
+		}
+	}
+
+	pub fn initial(&self) -> &Board {
+		&self.initial
+	}
+
+	pub fn steps(&self) -> &[ReplayStep] {
+		&self.steps
+	}
+
+	pub fn len(&self) -> usize {
+		self.steps.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.steps.is_empty()
+	}
+
+	// Record a move that changed the board
+	pub fn record(&mut self, direction: Direction, spawn: Option<Spawn>) {
+		self.steps.push(ReplayStep { direction, spawn });
+		self.undone.clear();
+	}
+
+	// Drop the last move, keeping it for `redo`
+	pub fn undo(&mut self) {
+		if let Some(step) = self.steps.pop() {
+			self.undone.push(step);
+		}
+	}
+
+	// Record the last undone move again
+	pub fn redo(&mut self) {
+		if let Some(step) = self.undone.pop() {
+			self.steps.push(step);
+		}
+	}
+
+	// The board after the first `moves` moves
+	pub fn board_at(&self, moves: usize) -> Board {
+		let mut board = self.initial.clone();
+
+		for step in self.steps.iter().take(moves) {
+			step.apply(&mut board);
+		}
+
+		board
+	}
+
+	// The board after every recorded move
+	pub fn final_board(&self) -> Board {
+		self.board_at(self.steps.len())
+	}
+
+	// Whether this recording leads to the given board, so it can be continued when a saved game is resumed
+	pub fn leads_to(&self, board: &Board) -> bool {
+		let last = self.final_board();
+
+		last.cells() == board.cells() && last.score() == board.score() && last.moves() == board.moves()
+	}
+}
+
+// Serialize a replay as the starting board in the save format followed by the moves
+pub fn write(replay: &Replay) -> Ini {
+	let mut ini = save::write(&replay.initial);
+
+	ini.set(REPLAY_SECTION, "version", REPLAY_VERSION);
+	ini.set(REPLAY_SECTION, "moves", write_steps(&replay.steps));
+
+	ini
+}
+
+// Deserialize a replay written by `write`, checking that every move can be played
+pub fn read(ini: &Ini) -> Result<Replay, SaveError> {
+	let version: u32 = ini.get(REPLAY_SECTION, "version")
+		.ok_or(SaveError::MissingVersion)?
+		.parse()
+		.map_err(|_| SaveError::Invalid("version"))?;
+
+	if version > REPLAY_VERSION {
+		return Err(SaveError::UnsupportedVersion(version));
+	}
+
+	let initial = save::read(ini)?;
+	let steps = read_steps(ini.get(REPLAY_SECTION, "moves").ok_or(SaveError::Missing("moves"))?)?;
+
+	// Make sure the whole game can be played back
+	let mut board = initial.clone();
+	for step in &steps {
+		step.apply(&mut board).ok_or(SaveError::Invalid("moves"))?;
+	}
+
+	Ok(Replay {
+		initial,
+		steps,
+		undone: Vec::new()
+	})
+}
+
+// Moves are stored as a space separated list of `direction:x:y:value`, where the direction is one of
+// L, R, U or D and the rest describes the spawned tile. A move without a spawn is just the direction.
+fn write_steps(steps: &[ReplayStep]) -> String {
+	steps
+		.iter()
+		.map(|step| {
+			let direction = match step.direction {
+				Direction::Left => "L",
+				Direction::Right => "R",
+				Direction::Up => "U",
+				Direction::Down => "D"
+			};
+
+			match step.spawn {
+				Some(spawn) => format!("{}:{}:{}:{}", direction, spawn.x, spawn.y, spawn.value),
+				None => direction.to_string()
+			}
+		})
+		.collect::<Vec<String>>()
+		.join(" ")
+}
+
+fn read_steps(input: &str) -> Result<Vec<ReplayStep>, SaveError> {
+	input
+		.split_whitespace()
+		.map(|token| {
+			let mut parts = token.split(':');
+
+			let direction = match parts.next() {
+				Some("L") => Direction::Left,
+				Some("R") => Direction::Right,
+				Some("U") => Direction::Up,
+				Some("D") => Direction::Down,
+				_ => return Err(SaveError::Invalid("moves"))
+			};
+
+			let numbers = parts
+				.map(|part| part.parse::<usize>().map_err(|_| SaveError::Invalid("moves")))
+				.collect::<Result<Vec<usize>, SaveError>>()?;
+
+			let spawn = match numbers[..] {
+				[] => None,
+				[x, y, value] if value <= MAX_VALUE => Some(Spawn { x, y, value }),
+				_ => return Err(SaveError::Invalid("moves"))
+			};
+
+			Ok(ReplayStep { direction, spawn })
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// A few moves of a seeded game, recorded the way the game records them
+	fn recorded_game() -> (Replay, Board) {
+		let mut board = Board::with_seed(4, 4, 11);
+		board.spawn_tile();
+
+		let mut replay = Replay::new(&board);
+
+		for &direction in [Direction::Left, Direction::Down, Direction::Right, Direction::Up, Direction::Left].iter() {
+			let outcome = board.play(direction);
+
+			if outcome.moved() {
+				replay.record(direction, outcome.spawned);
+			}
+		}
+
+		(replay, board)
+	}
+
+	#[test]
+	fn round_trip_leads_to_the_board() {
+		let (replay, board) = recorded_game();
+		let loaded = read(&Ini::parse(&write(&replay).to_string())).unwrap();
+
+		assert!(!loaded.is_empty());
+		assert_eq!(loaded.steps(), replay.steps());
+		assert!(loaded.leads_to(&board));
+		assert!(!loaded.leads_to(&loaded.board_at(1)));
+	}
+
+	#[test]
+	fn rejects_a_tampered_step() {
+		let (replay, _) = recorded_game();
+		let mut ini = write(&replay);
+		let mut steps = replay.steps().to_vec();
+
+		// Spawn the first tile off the board
+		if let Some(spawn) = &mut steps[0].spawn {
+			spawn.x = 4;
+		}
+
+		ini.set(REPLAY_SECTION, "moves", write_steps(&steps));
+
+		assert_eq!(read(&ini).err(), Some(SaveError::Invalid("moves")));
+	}
+
+	#[test]
+	fn rejects_out_of_range_values() {
+		let (replay, _) = recorded_game();
+		let mut ini = write(&replay);
+		let mut steps = replay.steps().to_vec();
+
+		if let Some(spawn) = &mut steps[0].spawn {
+			spawn.value = MAX_VALUE + 1;
+		}
+
+		ini.set(REPLAY_SECTION, "moves", write_steps(&steps));
+
+		assert_eq!(read(&ini).err(), Some(SaveError::Invalid("moves")));
+	}
+}
@@ -0,0 +1,55 @@
+use rand::{thread_rng, Error, Rng, RngCore};
+
+// Small SplitMix64 generator. Its whole state is a single `u64`, which makes it trivial to store
+// in a save file and resume exactly where it left off.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRng {
+	state: u64
+}
+
+impl GameRng {
+	pub fn from_state(state: u64) -> Self {
+		GameRng { state }
+	}
+
+	// Create a generator from a seed. The same seed always gives the same sequence.
+	pub fn from_seed(seed: u64) -> Self {
+		GameRng::from_state(seed)
+	}
+
+	pub fn state(&self) -> u64 {
+		self.state
+	}
+}
+
+impl RngCore for GameRng {
+	fn next_u32(&mut self) -> u32 {
+		(self.next_u64() >> 32) as u32
+	}
+
+	fn next_u64(&mut self) -> u64 {
+		self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+		let mut z = self.state;
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+		z ^ (z >> 31)
+	}
+
+	fn fill_bytes(&mut self, dest: &mut [u8]) {
+		for chunk in dest.chunks_mut(8) {
+			let bytes = self.next_u64().to_le_bytes();
+			chunk.copy_from_slice(&bytes[..chunk.len()]);
+		}
+	}
+
+	fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+		self.fill_bytes(dest);
+		Ok(())
+	}
+}
+
+// Pick a random seed for a new game
+pub fn random_seed() -> u64 {
+	thread_rng().gen()
+}
@@ -0,0 +1,237 @@
+use std::fmt;
+
+use crate::{
+	engine::{Board, EMPTY, MAX_SIZE, MAX_VALUE, MIN_SIZE},
+	ini::Ini,
+	rng::GameRng
+};
+
+// Version written to new save files. Bump this whenever the format changes and keep reading the
+// older versions in `read`.
+pub const SAVE_VERSION: u32 = 4;
+
+const SAVE_SECTION: &str = "Save";
+const GAME_SECTION: &str = "Game";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SaveError {
+	MissingVersion,
+	UnsupportedVersion(u32),
+	Missing(&'static str),
+	Invalid(&'static str)
+}
+
+impl fmt::Display for SaveError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			SaveError::MissingVersion => write!(f, "not a save file (no version)"),
+			SaveError::UnsupportedVersion(version) => write!(
+				f,
+				"save file version {} is newer than the supported version {}",
+				version,
+				SAVE_VERSION
+			),
+			SaveError::Missing(key) => write!(f, "missing `{}`", key),
+			SaveError::Invalid(key) => write!(f, "invalid `{}`", key)
+		}
+	}
+}
+
+// Serialize the full state of a game
+pub fn write(board: &Board) -> Ini {
+	let mut ini = Ini::new();
+
+	ini.set(SAVE_SECTION, "version", SAVE_VERSION);
+
+	ini.set(GAME_SECTION, "width", board.width());
+	ini.set(GAME_SECTION, "height", board.height());
+	ini.set(GAME_SECTION, "score", board.score());
+	ini.set(GAME_SECTION, "moves", board.moves());
+	ini.set(GAME_SECTION, "seed", board.seed());
+	ini.set(GAME_SECTION, "rng", board.rng().state());
+	ini.set(GAME_SECTION, "continued", board.is_continued());
+	ini.set(GAME_SECTION, "cells", write_cells(board));
+
+	ini
+}
+
+// Deserialize a game written by `write` from this or any older version
+pub fn read(ini: &Ini) -> Result<Board, SaveError> {
+	let version: u32 = ini.get(SAVE_SECTION, "version")
+		.ok_or(SaveError::MissingVersion)?
+		.parse()
+		.map_err(|_| SaveError::Invalid("version"))?;
+
+	match version {
+		1..=4 => read_game(ini, version),
+		_ => Err(SaveError::UnsupportedVersion(version))
+	}
+}
+
+fn read_game(ini: &Ini, version: u32) -> Result<Board, SaveError> {
+	// Boards were always square before version 4
+	let (width, height): (usize, usize) = if version >= 4 {
+		(get(ini, "width")?, get(ini, "height")?)
+	}
+	else {
+		let size = get(ini, "size")?;
+		(size, size)
+	};
+	let score = get(ini, "score")?;
+	let moves = get(ini, "moves")?;
+	let rng = GameRng::from_state(get(ini, "rng")?);
+
+	// Version 1 saves predate seeds, the generator state is the closest thing to one
+	let seed = if version >= 2 { get(ini, "seed")? } else { rng.state() };
+
+	let valid_size = |size: usize| (MIN_SIZE..=MAX_SIZE).contains(&size);
+
+	if !valid_size(width) || !valid_size(height) {
+		return Err(SaveError::Invalid("size"));
+	}
+
+	let cells = read_cells(ini.get(GAME_SECTION, "cells").ok_or(SaveError::Missing("cells"))?, width, height)?;
+
+	let mut board = Board::with_state(cells, score, moves, seed, rng);
+
+	// Before version 3 there was no way to keep playing after winning
+	if version >= 3 && get(ini, "continued")? {
+		board.keep_playing();
+	}
+
+	Ok(board)
+}
+
+fn get<T: std::str::FromStr>(ini: &Ini, key: &'static str) -> Result<T, SaveError> {
+	ini.get(GAME_SECTION, key)
+		.ok_or(SaveError::Missing(key))?
+		.parse()
+		.map_err(|_| SaveError::Invalid(key))
+}
+
+// Cells are stored row by row as a comma separated list of exponents, with `-` for empty cells
+fn write_cells(board: &Board) -> String {
+	board.cells()
+		.iter()
+		.flatten()
+		.map(|&value| if value == EMPTY { "-".to_string() } else { value.to_string() })
+		.collect::<Vec<String>>()
+		.join(",")
+}
+
+fn read_cells(input: &str, width: usize, height: usize) -> Result<Vec<Vec<usize>>, SaveError> {
+	let values = input
+		.split(',')
+		.map(|value| match value.trim() {
+			"-" => Ok(EMPTY),
+			value => value.parse::<usize>()
+				.ok()
+				.filter(|&value| value <= MAX_VALUE)
+				.ok_or(SaveError::Invalid("cells"))
+		})
+		.collect::<Result<Vec<usize>, SaveError>>()?;
+
+	if values.len() != width * height {
+		return Err(SaveError::Invalid("cells"));
+	}
+
+	Ok(values.chunks(width).map(|row| row.to_vec()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const E: usize = EMPTY;
+
+	// A 4x4 game as every version wrote it, with the first row filled in
+	const VERSION_1: &str = "[Save]\nversion = 1\n\n[Game]\nsize = 4\nscore = 12\nmoves = 3\nrng = 77\ncells = 0,1,-,2,-,-,-,-,-,-,-,-,-,-,-,-\n";
+	const VERSION_2: &str = "[Save]\nversion = 2\n\n[Game]\nsize = 4\nscore = 12\nmoves = 3\nseed = 5\nrng = 77\ncells = 0,1,-,2,-,-,-,-,-,-,-,-,-,-,-,-\n";
+	const VERSION_3: &str = "[Save]\nversion = 3\n\n[Game]\nsize = 4\nscore = 12\nmoves = 3\nseed = 5\nrng = 77\ncontinued = true\ncells = 0,1,-,2,-,-,-,-,-,-,-,-,-,-,-,-\n";
+
+	fn first_row(board: &Board) -> &[usize] {
+		&board.cells()[0]
+	}
+
+	#[test]
+	fn reads_version_1() {
+		let board = read(&Ini::parse(VERSION_1)).unwrap();
+
+		assert_eq!((board.width(), board.height()), (4, 4));
+		assert_eq!(first_row(&board), &[0, 1, E, 2]);
+		assert_eq!((board.score(), board.moves()), (12, 3));
+		// The generator state stands in for the missing seed
+		assert_eq!(board.seed(), 77);
+		assert_eq!(board.rng().state(), 77);
+		assert!(!board.is_continued());
+	}
+
+	#[test]
+	fn reads_version_2() {
+		let board = read(&Ini::parse(VERSION_2)).unwrap();
+
+		assert_eq!(first_row(&board), &[0, 1, E, 2]);
+		assert_eq!(board.seed(), 5);
+		assert_eq!(board.rng().state(), 77);
+		assert!(!board.is_continued());
+	}
+
+	#[test]
+	fn reads_version_3() {
+		let board = read(&Ini::parse(VERSION_3)).unwrap();
+
+		assert_eq!(first_row(&board), &[0, 1, E, 2]);
+		assert_eq!(board.seed(), 5);
+		assert!(board.is_continued());
+	}
+
+	#[test]
+	fn version_4_round_trips() {
+		let cells = vec![vec![0, E, 3], vec![E, 5, E], vec![1, 1, E], vec![E, E, MAX_VALUE]];
+		let mut board = Board::with_state(cells, 96, 14, 9, GameRng::from_state(123));
+		board.keep_playing();
+
+		let loaded = read(&Ini::parse(&write(&board).to_string())).unwrap();
+
+		assert_eq!(loaded.cells(), board.cells());
+		assert_eq!((loaded.score(), loaded.moves(), loaded.seed()), (96, 14, 9));
+		assert_eq!(loaded.rng(), board.rng());
+		assert!(loaded.is_continued());
+	}
+
+	#[test]
+	fn rejects_out_of_range_cells() {
+		let mut ini = write(&Board::with_seed(4, 4, 0));
+
+		ini.set(GAME_SECTION, "cells", "0,-,-,-,-,-,-,-,-,-,-,-,-,-,-,27");
+		assert_eq!(read(&ini).err(), Some(SaveError::Invalid("cells")));
+
+		ini.set(GAME_SECTION, "cells", "0,-,-,-,-,-,-,-,-,-,-,-,-,-,-,-1");
+		assert_eq!(read(&ini).err(), Some(SaveError::Invalid("cells")));
+
+		ini.set(GAME_SECTION, "cells", "0,-,-");
+		assert_eq!(read(&ini).err(), Some(SaveError::Invalid("cells")));
+	}
+
+	#[test]
+	fn rejects_unsupported_sizes() {
+		let mut ini = write(&Board::with_seed(4, 4, 0));
+
+		ini.set(GAME_SECTION, "width", 1);
+		ini.set(GAME_SECTION, "height", 2);
+		ini.set(GAME_SECTION, "cells", "40,40");
+		assert_eq!(read(&ini).err(), Some(SaveError::Invalid("size")));
+
+		ini.set(GAME_SECTION, "width", MAX_SIZE + 1);
+		ini.set(GAME_SECTION, "height", 4);
+		assert_eq!(read(&ini).err(), Some(SaveError::Invalid("size")));
+	}
+
+	#[test]
+	fn rejects_newer_versions() {
+		let mut ini = write(&Board::with_seed(4, 4, 0));
+		ini.set(SAVE_SECTION, "version", SAVE_VERSION + 1);
+
+		assert_eq!(read(&ini).err(), Some(SaveError::UnsupportedVersion(SAVE_VERSION + 1)));
+	}
+}
@@ -0,0 +1,214 @@
+use rand::RngCore;
+use std::{env, fs, path::PathBuf, time::Instant};
+
+use twenty::{
+	rng::{self, GameRng},
+	simulation::{self, GameResult, Summary},
+	strategy
+};
+
+use crate::{
+	options::parse_board_size,
+	playboard::PlayBoard,
+	user_data::UserData
+};
+
+const USAGE: &str = "Usage: twenty simulate [options]
+
+Plays games without opening a window and prints statistics about them.
+
+Options:
+	--games <number>       Number of games to play, 100 by default
+	--strategy <name>      How moves are picked: random, greedy, corner or expectimax. Random by default.
+	--depth <number>       Moves the expectimax strategy looks ahead, ai_depth from the settings by default
+	--seed <number>        Seed the seeds of the games are drawn from, random by default
+	--game-seed <number>   Play a single game with this seed, such as one from the csv or json results
+	--size <size>          Board size such as 4 or 5x3, board_size from the settings by default
+	--format <format>      Output format: text, csv or json. Text by default.
+	--output <path>        Write the csv or json output to a file instead of printing it";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+	Text,
+	Csv,
+	Json
+}
+
+// Command line options of the simulate command
+#[derive(Debug)]
+struct SimulateOptions {
+	games: usize,
+	strategy: String,
+	depth: Option<usize>,
+	seed: Option<u64>,
+	game_seed: Option<u64>,
+	size: Option<(usize, usize)>,
+	format: Format,
+	output: Option<PathBuf>
+}
+
+impl Default for SimulateOptions {
+	fn default() -> Self {
+		SimulateOptions {
+			games: 100,
+			strategy: "random".to_string(),
+			depth: None,
+			seed: None,
+			game_seed: None,
+			size: None,
+			format: Format::Text,
+			output: None
+		}
+	}
+}
+
+impl SimulateOptions {
+	fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+		let mut options = SimulateOptions::default();
+
+		while let Some(arg) = args.next() {
+			let mut value = |name: &str| args.next().ok_or(format!("Missing value for {}", name));
+
+			match arg.as_str() {
+				"--games" => {
+					let games = value("--games")?;
+					options.games = games.parse().map_err(|_| format!("Invalid number of games `{}`", games))?;
+				},
+				"--strategy" => {
+					let strategy = value("--strategy")?;
+
+					if !strategy::NAMES.contains(&strategy.as_str()) {
+						return Err(format!("Unknown strategy `{}`, expected one of {}", strategy, strategy::NAMES.join(", ")));
+					}
+
+					options.strategy = strategy;
+				},
+				"--depth" => {
+					let depth = value("--depth")?;
+					options.depth = Some(depth.parse().map_err(|_| format!("Invalid depth `{}`", depth))?);
+				},
+				"--seed" => {
+					let seed = value("--seed")?;
+					options.seed = Some(seed.parse().map_err(|_| format!("Invalid seed `{}`", seed))?);
+				},
+				"--game-seed" => {
+					let seed = value("--game-seed")?;
+					options.game_seed = Some(seed.parse().map_err(|_| format!("Invalid seed `{}`", seed))?);
+				},
+				"--size" => {
+					let size = value("--size")?;
+
+					options.size = Some(parse_board_size(&size).ok_or(format!(
+						"Invalid board size `{}`, expected sizes from {} to {} such as 4 or 5x3",
+						size,
+						PlayBoard::MIN_SIZE,
+						PlayBoard::MAX_SIZE
+					))?);
+				},
+				"--format" => {
+					options.format = match value("--format")?.as_str() {
+						"text" => Format::Text,
+						"csv" => Format::Csv,
+						"json" => Format::Json,
+						format => return Err(format!("Unknown format `{}`, expected text, csv or json", format))
+					};
+				},
+				"--output" => {
+					options.output = Some(PathBuf::from(value("--output")?));
+				},
+				"--help" | "-h" => {
+					println!("{}", USAGE);
+					std::process::exit(0);
+				},
+				_ => return Err(format!("Unknown argument `{}`", arg))
+			}
+		}
+
+		Ok(options)
+	}
+}
+
+// Run the simulate command with the arguments following `simulate`
+pub fn run<I: Iterator<Item = String>>(args: I) {
+	let options = match SimulateOptions::parse(args) {
+		Ok(options) => options,
+		Err(err) => {
+			println!("{}\n\n{}", err, USAGE);
+			std::process::exit(2);
+		}
+	};
+
+	let user_data = UserData::load();
+	let depth = options.depth.unwrap_or_else(|| user_data.ai_depth());
+	let mut strategy = strategy::by_name(&options.strategy, depth).expect("strategy name was checked while parsing");
+
+	// Only expectimax searches ahead, the other strategies ignore the depth
+	let depth = Some(depth).filter(|_| options.strategy == "expectimax");
+	let size = options.size.unwrap_or_else(|| user_data.board_size());
+	let seed = options.game_seed.or(options.seed).unwrap_or_else(rng::random_seed);
+
+	// Every game gets its own seed drawn from the main one, a single game can be played again from
+	// its own seed with --game-seed
+	let seeds: Vec<u64> = match options.game_seed {
+		Some(game_seed) => vec![game_seed],
+		None => {
+			let mut seeds = GameRng::from_seed(seed);
+			(0..options.games).map(|_| seeds.next_u64()).collect()
+		}
+	};
+	let start = Instant::now();
+
+	let results: Vec<GameResult> = seeds
+		.into_iter()
+		.map(|seed| simulation::play_game(user_data.new_game(size, Some(seed)), strategy.as_mut()))
+		.collect();
+
+	let summary = Summary::new(&results, start.elapsed());
+
+	let output = match options.format {
+		Format::Text => None,
+		Format::Csv => Some(simulation::to_csv(&results)),
+		Format::Json => Some(simulation::to_json(strategy.name(), depth, seed, &summary, &results))
+	};
+
+	match (output, &options.output) {
+		(Some(output), Some(path)) => {
+			if let Err(err) = fs::write(path, output) {
+				println!("Failed to write results to {}: {}", path.display(), err);
+				std::process::exit(1);
+			}
+
+			print_summary(strategy.name(), depth, seed, size, &summary);
+		},
+		(Some(output), None) => print!("{}", output),
+		(None, _) => print_summary(strategy.name(), depth, seed, size, &summary)
+	}
+}
+
+fn print_summary(strategy: &str, depth: Option<usize>, seed: u64, (width, height): (usize, usize), summary: &Summary) {
+	let depth = depth.map_or(String::new(), |depth| format!(" (depth {})", depth));
+
+	println!("Played {} games on a {}x{} board with the {} strategy{}, seed {}", summary.games, width, height, strategy, depth, seed);
+	println!();
+	println!("Average score:     {:.1}", summary.average_score);
+	println!("Median score:      {:.1}", summary.median_score);
+	println!("Best score:        {}", summary.best_score);
+	println!("Average moves:     {:.1}", summary.average_moves);
+	println!("Games per second:  {:.1}", summary.games_per_second());
+	println!();
+	println!("{:>8}  {:>8}  {:>7}", "Max tile", "Games", "Percent");
+
+	for &(value, count) in &summary.max_tiles {
+		println!(
+			"{:>8}  {:>8}  {:>6.1}%",
+			2u64.pow(value as u32 + 1),
+			count,
+			100.0 * count as f64 / summary.games as f64
+		);
+	}
+}
+
+// Whether the process was started as `twenty simulate`
+pub fn requested() -> bool {
+	env::args().nth(1).is_some_and(|arg| arg == "simulate")
+}
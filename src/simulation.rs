@@ -0,0 +1,198 @@
+use std::time::Duration;
+
+use crate::{
+	engine::Board,
+	strategy::{self, Strategy}
+};
+
+// Final state of a single simulated game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameResult {
+	pub seed: u64,
+	pub score: u32,
+	pub moves: u32,
+	// Exponent of the highest tile, if there were any tiles
+	pub max_tile: Option<usize>
+}
+
+// Play a game to the end, picking every move with `strategy`. Reaching the target tile does not end
+// the game.
+pub fn play_game(mut board: Board, strategy: &mut dyn Strategy) -> GameResult {
+	strategy::play_to_end(&mut board, strategy);
+
+	GameResult {
+		seed: board.seed(),
+		score: board.score(),
+		moves: board.moves(),
+		max_tile: board.max_tile()
+	}
+}
+
+// Statistics over a batch of simulated games
+#[derive(Debug, Clone)]
+pub struct Summary {
+	pub games: usize,
+	pub average_score: f64,
+	pub median_score: f64,
+	pub best_score: u32,
+	pub average_moves: f64,
+	// Number of games that ended with each highest tile exponent, lowest first
+	pub max_tiles: Vec<(usize, usize)>,
+	pub elapsed: Duration
+}
+
+impl Summary {
+	pub fn new(results: &[GameResult], elapsed: Duration) -> Self {
+		let games = results.len();
+		let mut scores: Vec<u32> = results.iter().map(|result| result.score).collect();
+		scores.sort_unstable();
+
+		let median_score = match games {
+			0 => 0.0,
+			_ if games % 2 == 0 => (scores[games / 2 - 1] as f64 + scores[games / 2] as f64) / 2.0,
+			_ => scores[games / 2] as f64
+		};
+
+		let mut max_tiles: Vec<(usize, usize)> = Vec::new();
+
+		for value in results.iter().filter_map(|result| result.max_tile) {
+			match max_tiles.iter_mut().find(|(tile, _)| *tile == value) {
+				Some((_, count)) => *count += 1,
+				None => max_tiles.push((value, 1))
+			}
+		}
+
+		max_tiles.sort_unstable();
+
+		Summary {
+			games,
+			average_score: mean(results.iter().map(|result| result.score)),
+			median_score,
+			best_score: scores.last().copied().unwrap_or(0),
+			average_moves: mean(results.iter().map(|result| result.moves)),
+			max_tiles,
+			elapsed
+		}
+	}
+
+	pub fn games_per_second(&self) -> f64 {
+		self.games as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+	}
+}
+
+fn mean<I: ExactSizeIterator<Item = u32>>(values: I) -> f64 {
+	let count = values.len();
+
+	if count == 0 { 0.0 } else { values.map(f64::from).sum::<f64>() / count as f64 }
+}
+
+// Value of the tile with the given exponent, 0 for a board without tiles
+fn tile_value(max_tile: Option<usize>) -> u64 {
+	max_tile.map_or(0, |value| 2u64.pow(value as u32 + 1))
+}
+
+// One line per game, with a header
+pub fn to_csv(results: &[GameResult]) -> String {
+	let mut res = String::from("game,seed,score,moves,max_tile\n");
+
+	for (i, result) in results.iter().enumerate() {
+		res += &format!(
+			"{},{},{},{},{}\n",
+			i + 1,
+			result.seed,
+			result.score,
+			result.moves,
+			tile_value(result.max_tile)
+		);
+	}
+
+	res
+}
+
+// The summary and every game as a single JSON object. `depth` is the search depth of strategies that
+// have one.
+pub fn to_json(strategy: &str, depth: Option<usize>, seed: u64, summary: &Summary, results: &[GameResult]) -> String {
+	let distribution: Vec<String> = summary.max_tiles
+		.iter()
+		.map(|&(value, count)| format!("\"{}\": {}", tile_value(Some(value)), count))
+		.collect();
+
+	let games: Vec<String> = results
+		.iter()
+		.map(|result| format!(
+			"    {{\"seed\": {}, \"score\": {}, \"moves\": {}, \"max_tile\": {}}}",
+			result.seed,
+			result.score,
+			result.moves,
+			tile_value(result.max_tile)
+		))
+		.collect();
+
+	let depth = depth.map_or("null".to_string(), |depth| depth.to_string());
+
+	format!(
+		"{{\n  \"strategy\": \"{}\",\n  \"depth\": {},\n  \"seed\": {},\n  \"games\": {},\n  \
+		\"average_score\": {},\n  \"median_score\": {},\n  \"best_score\": {},\n  \"average_moves\": {},\n  \
+		\"games_per_second\": {},\n  \"max_tiles\": {{{}}},\n  \"results\": [\n{}\n  ]\n}}\n",
+		strategy,
+		depth,
+		seed,
+		summary.games,
+		summary.average_score,
+		summary.median_score,
+		summary.best_score,
+		summary.average_moves,
+		summary.games_per_second(),
+		distribution.join(", "),
+		games.join(",\n")
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn results(scores: &[u32]) -> Vec<GameResult> {
+		scores
+			.iter()
+			.enumerate()
+			.map(|(i, &score)| GameResult { seed: i as u64, score, moves: score / 4, max_tile: Some(score as usize % 3) })
+			.collect()
+	}
+
+	#[test]
+	fn median_of_an_odd_number_of_games() {
+		let summary = Summary::new(&results(&[40, 8, 16]), Duration::from_secs(1));
+
+		assert_eq!(summary.median_score, 16.0);
+		assert_eq!(summary.best_score, 40);
+	}
+
+	#[test]
+	fn median_of_an_even_number_of_games() {
+		let summary = Summary::new(&results(&[40, 8, 16, 100]), Duration::from_secs(1));
+
+		assert_eq!(summary.median_score, 28.0);
+		assert_eq!(summary.average_score, 41.0);
+		assert_eq!(summary.average_moves, 10.25);
+	}
+
+	#[test]
+	fn max_tiles_are_counted_lowest_first() {
+		// Max tiles 1, 2, 1 and 0
+		let summary = Summary::new(&results(&[40, 8, 16, 3]), Duration::from_secs(2));
+
+		assert_eq!(summary.max_tiles, vec![(0, 1), (1, 2), (2, 1)]);
+		assert_eq!(summary.games_per_second(), 2.0);
+	}
+
+	#[test]
+	fn no_games() {
+		let summary = Summary::new(&[], Duration::from_secs(1));
+
+		assert_eq!(summary.games, 0);
+		assert_eq!(summary.median_score, 0.0);
+		assert_eq!(summary.best_score, 0);
+		assert!(summary.max_tiles.is_empty());
+	}
+}
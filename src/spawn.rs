@@ -0,0 +1,139 @@
+use rand::Rng;
+use std::fmt;
+
+use crate::{engine::MAX_VALUE, rng::GameRng};
+
+// Decides where new tiles spawn and what value they get
+pub trait SpawnPolicy: fmt::Debug + Send + Sync {
+	// Index into `empty` of the cell to spawn in. `empty` is never empty.
+	fn choose_cell(&self, empty: &[(usize, usize)], rng: &mut GameRng) -> usize {
+		rng.gen_range(0, empty.len())
+	}
+
+	// Exponent of the spawned tile, where 0 is a 2 tile
+	fn choose_value(&self, rng: &mut GameRng) -> usize;
+
+	// Every exponent `choose_value` can return with its probability, used by the AI to look ahead
+	fn value_probabilities(&self) -> Vec<(usize, f64)>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpawnError {
+	// A pair that isn't written as `value:weight`
+	Malformed(String),
+	// A value that isn't a power of 2 from 2 upwards
+	InvalidValue(u32),
+	// A value above the largest tile a board can hold
+	ValueTooLarge(u32),
+	// No weights, or only weights of 0
+	NoWeights,
+	// The weights add up to more than fits in a u32
+	TotalTooLarge
+}
+
+impl fmt::Display for SpawnError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			SpawnError::Malformed(pair) => write!(f, "`{}` is not a value:weight pair", pair),
+			SpawnError::InvalidValue(value) => write!(f, "{} is not a tile value", value),
+			SpawnError::ValueTooLarge(value) => write!(f, "{} is above the largest tile {}", value, 2u64.pow(MAX_VALUE as u32 + 1)),
+			SpawnError::NoWeights => write!(f, "no tile has a weight above 0"),
+			SpawnError::TotalTooLarge => write!(f, "the weights add up to more than {}", u32::MAX)
+		}
+	}
+}
+
+// Spawns in a uniformly random empty cell, picking the value from a weighted table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeightedSpawn {
+	// (exponent, weight) pairs
+	weights: Vec<(usize, u32)>,
+	total: u32
+}
+
+impl WeightedSpawn {
+	// Fails if there are no weights, they are all 0, their total does not fit in a u32 or a value is
+	// above `MAX_VALUE`
+	pub fn new(weights: Vec<(usize, u32)>) -> Result<Self, SpawnError> {
+		if let Some(&(value, _)) = weights.iter().find(|&&(value, _)| value > MAX_VALUE) {
+			return Err(SpawnError::ValueTooLarge(2u32.saturating_pow(value as u32 + 1)));
+		}
+
+		let total = weights.iter()
+			.try_fold(0u32, |total, &(_, weight)| total.checked_add(weight))
+			.ok_or(SpawnError::TotalTooLarge)?;
+
+		if total == 0 {
+			return Err(SpawnError::NoWeights);
+		}
+
+		Ok(WeightedSpawn { weights, total })
+	}
+
+	// The classic 2048 rules, 90% 2 tiles and 10% 4 tiles
+	pub fn classic() -> Self {
+		WeightedSpawn {
+			weights: vec![(0, 9), (1, 1)],
+			total: 10
+		}
+	}
+
+	// Parse a list of `value:weight` pairs using tile values, such as "2:9, 4:1"
+	pub fn parse(input: &str) -> Result<Self, SpawnError> {
+		let weights = input
+			.split(',')
+			.map(|pair| {
+				let malformed = || SpawnError::Malformed(pair.trim().to_string());
+				let parts: Vec<&str> = pair.split(':').map(str::trim).collect();
+
+				let (value, weight): (u32, u32) = match parts[..] {
+					[value, weight] => (value.parse().map_err(|_| malformed())?, weight.parse().map_err(|_| malformed())?),
+					_ => return Err(malformed())
+				};
+
+				// Only powers of 2 from 2 upwards are valid tile values
+				if value < 2 || !value.is_power_of_two() {
+					return Err(SpawnError::InvalidValue(value));
+				}
+
+				Ok((value.trailing_zeros() as usize - 1, weight))
+			})
+			.collect::<Result<Vec<(usize, u32)>, SpawnError>>()?;
+
+		WeightedSpawn::new(weights)
+	}
+
+	pub fn weights(&self) -> &[(usize, u32)] {
+		&self.weights
+	}
+}
+
+impl Default for WeightedSpawn {
+	fn default() -> Self {
+		WeightedSpawn::classic()
+	}
+}
+
+impl SpawnPolicy for WeightedSpawn {
+	fn choose_value(&self, rng: &mut GameRng) -> usize {
+		let mut roll = rng.gen_range(0, self.total);
+
+		for &(value, weight) in &self.weights {
+			if roll < weight {
+				return value;
+			}
+
+			roll -= weight;
+		}
+
+		unreachable!("roll is always below the total weight")
+	}
+
+	fn value_probabilities(&self) -> Vec<(usize, f64)> {
+		self.weights
+			.iter()
+			.filter(|&&(_, weight)| weight > 0)
+			.map(|&(value, weight)| (value, weight as f64 / self.total as f64))
+			.collect()
+	}
+}
@@ -0,0 +1,138 @@
+use rand::Rng;
+
+use crate::{
+	ai::Expectimax,
+	engine::{Board, Direction},
+	rng::GameRng
+};
+
+// A bot that plays the game. Implement this to let your own bot play in the window with autoplay or
+// in the `simulate` command, and add it to `by_name` to make it selectable. The window runs
+// strategies on a worker thread, so they have to be `Send`.
+pub trait Strategy: Send {
+	// Short name used to select the strategy, such as "random"
+	fn name(&self) -> &str;
+
+	// Called before a game starts, so strategies with state can reset it
+	fn new_game(&mut self, _board: &Board) {}
+
+	// The next move to play, or `None` to give up
+	fn next_move(&mut self, board: &Board) -> Option<Direction>;
+}
+
+// Names of the built-in strategies accepted by `by_name`
+pub const NAMES: [&str; 4] = ["random", "greedy", "corner", "expectimax"];
+
+// Create a built-in strategy by name, `depth` is only used by expectimax
+pub fn by_name(name: &str, depth: usize) -> Option<Box<dyn Strategy>> {
+	match name {
+		"random" => Some(Box::new(RandomMoves::new(0))),
+		"greedy" => Some(Box::new(Greedy)),
+		"corner" => Some(Box::new(Corner)),
+		"expectimax" => Some(Box::new(Expectimax::new(depth))),
+		_ => None
+	}
+}
+
+// Play until the game is over or the strategy gives up, returns the number of moves played. A move
+// that does not change the board counts as giving up.
+pub fn play_to_end(board: &mut Board, strategy: &mut dyn Strategy) -> u32 {
+	let mut moves = 0;
+
+	strategy.new_game(board);
+
+	while !board.is_game_over() {
+		match strategy.next_move(board) {
+			Some(direction) if board.play(direction).moved() => moves += 1,
+			_ => break
+		}
+	}
+
+	moves
+}
+
+// Every move that changes the board, with the board after it and the score it gained
+fn possible_moves(board: &Board) -> impl Iterator<Item = (Direction, Board, u32)> + '_ {
+	Direction::ALL.iter().filter_map(move |&direction| {
+		let mut next = board.clone();
+		let outcome = next.slide(direction);
+
+		if outcome.moved() { Some((direction, next, outcome.score)) } else { None }
+	})
+}
+
+// Picks any move that changes the board. The moves are seeded from the board's seed at the start of
+// every game, so the same game always plays out the same way.
+#[derive(Debug, Clone)]
+pub struct RandomMoves {
+	rng: GameRng
+}
+
+impl RandomMoves {
+	pub fn new(seed: u64) -> Self {
+		RandomMoves { rng: GameRng::from_seed(seed) }
+	}
+}
+
+impl Strategy for RandomMoves {
+	fn name(&self) -> &str {
+		"random"
+	}
+
+	fn new_game(&mut self, board: &Board) {
+		self.rng = GameRng::from_seed(!board.seed());
+	}
+
+	fn next_move(&mut self, board: &Board) -> Option<Direction> {
+		let moves: Vec<Direction> = possible_moves(board).map(|(direction, _, _)| direction).collect();
+
+		if moves.is_empty() { None } else { Some(moves[self.rng.gen_range(0, moves.len())]) }
+	}
+}
+
+// Picks the move that scores the most right away, keeping more cells empty on a tie
+#[derive(Debug, Clone, Copy)]
+pub struct Greedy;
+
+impl Strategy for Greedy {
+	fn name(&self) -> &str {
+		"greedy"
+	}
+
+	fn next_move(&mut self, board: &Board) -> Option<Direction> {
+		possible_moves(board)
+			.max_by_key(|(_, next, score)| (*score, next.empty_cells().len()))
+			.map(|(direction, _, _)| direction)
+	}
+}
+
+// Keeps the highest tiles in the bottom left corner by preferring down, then left, then right
+#[derive(Debug, Clone, Copy)]
+pub struct Corner;
+
+impl Corner {
+	// Moves from most to least preferred
+	const ORDER: [Direction; 4] = [Direction::Down, Direction::Left, Direction::Right, Direction::Up];
+}
+
+impl Strategy for Corner {
+	fn name(&self) -> &str {
+		"corner"
+	}
+
+	fn next_move(&mut self, board: &Board) -> Option<Direction> {
+		let moves: Vec<Direction> = possible_moves(board).map(|(direction, _, _)| direction).collect();
+
+		Corner::ORDER.iter().copied().find(|direction| moves.contains(direction))
+	}
+}
+
+impl Strategy for Expectimax {
+	fn name(&self) -> &str {
+		"expectimax"
+	}
+
+	fn next_move(&mut self, board: &Board) -> Option<Direction> {
+		self.best_move(board)
+	}
+}
@@ -0,0 +1,111 @@
+use twenty::engine::Direction;
+
+// Distance in pixels the cursor has to be dragged for a swipe
+const MIN_DISTANCE: f64 = 30.0;
+// How many times further the cursor has to move along one axis than the other, so diagonal drags
+// don't slide the board in a direction the player didn't mean
+const DOMINANCE: f64 = 1.5;
+
+// Recognizes click and drag gestures with a mouse or touchpad as swipes
+pub struct Swipe {
+	cursor: [f64; 2],
+	// Where the button was pressed, while a drag is in progress
+	start: Option<[f64; 2]>
+}
+
+impl Swipe {
+	pub fn new() -> Self {
+		Swipe {
+			cursor: [0.0, 0.0],
+			start: None
+		}
+	}
+
+	pub fn move_cursor(&mut self, position: [f64; 2]) {
+		self.cursor = position;
+	}
+
+	// Start a drag if the cursor is inside the area, given as x, y, width and height
+	pub fn press(&mut self, [x, y, width, height]: [f64; 4]) {
+		let [cursor_x, cursor_y] = self.cursor;
+		let inside = cursor_x >= x && cursor_x <= x + width && cursor_y >= y && cursor_y <= y + height;
+
+		self.start = if inside { Some(self.cursor) } else { None };
+	}
+
+	// End the drag, returning the direction of the swipe if it was one
+	pub fn release(&mut self) -> Option<Direction> {
+		let [start_x, start_y] = self.start.take()?;
+		let dx = self.cursor[0] - start_x;
+		let dy = self.cursor[1] - start_y;
+
+		if dx.abs().max(dy.abs()) < MIN_DISTANCE {
+			return None;
+		}
+
+		if dx.abs() >= dy.abs() * DOMINANCE {
+			Some(if dx < 0.0 { Direction::Left } else { Direction::Right })
+		}
+		else if dy.abs() >= dx.abs() * DOMINANCE {
+			Some(if dy < 0.0 { Direction::Up } else { Direction::Down })
+		}
+		else {
+			None
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const AREA: [f64; 4] = [100.0, 100.0, 400.0, 400.0];
+
+	// Press at `from` and release at `to`
+	fn drag(from: [f64; 2], to: [f64; 2]) -> Option<Direction> {
+		let mut swipe = Swipe::new();
+		swipe.move_cursor(from);
+		swipe.press(AREA);
+		swipe.move_cursor(to);
+		swipe.release()
+	}
+
+	#[test]
+	fn swipes_in_every_direction() {
+		assert_eq!(drag([300.0, 300.0], [200.0, 310.0]), Some(Direction::Left));
+		assert_eq!(drag([300.0, 300.0], [400.0, 290.0]), Some(Direction::Right));
+		assert_eq!(drag([300.0, 300.0], [310.0, 200.0]), Some(Direction::Up));
+		assert_eq!(drag([300.0, 300.0], [290.0, 400.0]), Some(Direction::Down));
+	}
+
+	#[test]
+	fn short_drags_are_not_swipes() {
+		assert_eq!(drag([300.0, 300.0], [329.0, 300.0]), None);
+		assert_eq!(drag([300.0, 300.0], [330.0, 300.0]), Some(Direction::Right));
+	}
+
+	#[test]
+	fn diagonal_drags_are_not_swipes() {
+		assert_eq!(drag([300.0, 300.0], [400.0, 400.0]), None);
+		// One axis has to be moved along at least 1.5 times as far as the other
+		assert_eq!(drag([300.0, 300.0], [390.0, 239.0]), None);
+		assert_eq!(drag([300.0, 300.0], [390.0, 240.0]), Some(Direction::Right));
+	}
+
+	#[test]
+	fn presses_outside_the_area_are_ignored() {
+		assert_eq!(drag([50.0, 300.0], [300.0, 300.0]), None);
+		assert_eq!(drag([300.0, 501.0], [300.0, 200.0]), None);
+	}
+
+	#[test]
+	fn release_ends_the_drag() {
+		let mut swipe = Swipe::new();
+		swipe.move_cursor([300.0, 300.0]);
+		swipe.press(AREA);
+		swipe.move_cursor([400.0, 300.0]);
+
+		assert_eq!(swipe.release(), Some(Direction::Right));
+		assert_eq!(swipe.release(), None);
+	}
+}
@@ -0,0 +1,216 @@
+use std::fs;
+
+use twenty::ini::Ini;
+
+use crate::assets;
+use crate::utils::Rgba;
+
+pub const DEFAULT_THEME: &str = "classic";
+
+// Directory of theme files in the asset directory
+const THEME_DIR: &str = "themes";
+const THEME_SECTION: &str = "Theme";
+
+// How much darker each tile past the last color gets, and the darkest they get
+const DARKEN_STEP: f32 = 0.85;
+const MIN_BRIGHTNESS: f32 = 0.35;
+
+// Themes that are always available, a file with the same name in the theme directory replaces them
+const BUILT_IN: [(&str, &str); 3] = [
+	("classic", include_str!("../assets/themes/classic.ini")),
+	("dark", include_str!("../assets/themes/dark.ini")),
+	("high_contrast", include_str!("../assets/themes/high_contrast.ini"))
+];
+
+// Colors, fonts and tile images the window is drawn with
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+	// Name of the theme file without its extension, as saved in the settings
+	pub id: String,
+	// Name shown in game
+	pub name: String,
+	pub background: Rgba,
+	pub text: Rgba,
+	// Hints and the selected item of menus
+	pub accent: Rgba,
+	// Drawn over the game for the win and game over messages and the size menu
+	pub overlay: Rgba,
+	// Drawn over the game for the controls screen
+	pub menu_background: Rgba,
+	pub empty_tile: Rgba,
+	// Tiles from 2 upwards, higher tiles get darker versions of the last color
+	pub tiles: Vec<Rgba>,
+	pub tile_text_dark: Rgba,
+	pub tile_text_light: Rgba,
+	// Number of tiles from 2 upwards that use the dark text
+	pub dark_text_tiles: usize,
+	// Font of all text but the seed, and the font of the seed. Relative to the assets directory.
+	pub font: String,
+	pub mono_font: String,
+	// Directory with tile images to draw instead of the generated tiles, relative to the assets directory
+	pub tile_images: Option<String>
+}
+
+impl Theme {
+	// Load a theme by id, from the theme directory or the built-in themes. Unknown themes give the
+	// default theme and missing or invalid values are taken from it.
+	pub fn load(id: &str) -> Self {
+		let ini = match theme_ini(id) {
+			Some(ini) => ini,
+			None => {
+				println!("Unknown theme `{}`, using the {} theme", id, DEFAULT_THEME);
+				return Theme::default();
+			}
+		};
+
+		Theme::parse(id, &ini, &Theme::default())
+	}
+
+	fn parse(id: &str, ini: &Ini, defaults: &Theme) -> Self {
+		let color = |key: &str, default: Rgba| {
+			ini.get(THEME_SECTION, key).map_or(default, |value| {
+				parse_color(value).unwrap_or_else(|| {
+					println!("Invalid color `{}` for {} in the {} theme", value, key, id);
+					default
+				})
+			})
+		};
+
+		// A single invalid color leaves out the whole list, since the tiles after it would be off by one
+		let tiles = ini.get(THEME_SECTION, "tiles")
+			.and_then(|value| {
+				value.split(',')
+					.map(|color| {
+						let color = color.trim();
+
+						parse_color(color).or_else(|| {
+							println!("Invalid color `{}` in the tiles of the {} theme, using the default tiles", color, id);
+							None
+						})
+					})
+					.collect::<Option<Vec<Rgba>>>()
+			})
+			.unwrap_or_else(|| defaults.tiles.clone());
+
+		let string = |key: &str| ini.get(THEME_SECTION, key).map(str::to_string);
+
+		Theme {
+			id: id.to_string(),
+			name: string("name").unwrap_or_else(|| id.to_string()),
+			background: color("background", defaults.background),
+			text: color("text", defaults.text),
+			accent: color("accent", defaults.accent),
+			overlay: color("overlay", defaults.overlay),
+			menu_background: color("menu_background", defaults.menu_background),
+			empty_tile: color("empty_tile", defaults.empty_tile),
+			tiles,
+			tile_text_dark: color("tile_text_dark", defaults.tile_text_dark),
+			tile_text_light: color("tile_text_light", defaults.tile_text_light),
+			dark_text_tiles: ini.get_parsed(THEME_SECTION, "dark_text_tiles").unwrap_or(defaults.dark_text_tiles),
+			font: string("font").unwrap_or_else(|| defaults.font.clone()),
+			mono_font: string("mono_font").unwrap_or_else(|| defaults.mono_font.clone()),
+			tile_images: string("tile_images")
+		}
+	}
+
+	// Background of a tile value
+	pub fn tile_color(&self, value: usize) -> Rgba {
+		match self.tiles.get(value) {
+			Some(&color) => color,
+			None => {
+				let last = self.tiles.len() - 1;
+				let brightness = DARKEN_STEP.powi((value - last) as i32).max(MIN_BRIGHTNESS);
+				let [r, g, b, a] = self.tiles[last];
+
+				[r * brightness, g * brightness, b * brightness, a]
+			}
+		}
+	}
+
+	// Color of the number on a tile value
+	pub fn tile_text(&self, value: usize) -> Rgba {
+		if value < self.dark_text_tiles { self.tile_text_dark } else { self.tile_text_light }
+	}
+}
+
+impl Default for Theme {
+	fn default() -> Self {
+		let ini = Ini::parse(BUILT_IN[0].1);
+
+		// The classic theme defines every value, these are only used if it is edited to leave some out
+		let fallback = Theme {
+			id: DEFAULT_THEME.to_string(),
+			name: "Classic".to_string(),
+			background: [0.733, 0.678, 0.627, 1.0],
+			text: [0.463, 0.431, 0.400, 1.0],
+			accent: [0.965, 0.486, 0.373, 0.9],
+			overlay: [1.0, 1.0, 1.0, 0.5],
+			menu_background: [1.0, 1.0, 1.0, 0.85],
+			empty_tile: [0.804, 0.757, 0.706, 1.0],
+			tiles: vec![[0.933, 0.894, 0.855, 1.0]],
+			tile_text_dark: [0.467, 0.431, 0.396, 1.0],
+			tile_text_light: [0.976, 0.965, 0.949, 1.0],
+			dark_text_tiles: 2,
+			font: "Brandon_blk.ttf".to_string(),
+			mono_font: "FiraCode-Regular.ttf".to_string(),
+			tile_images: None
+		};
+
+		Theme::parse(DEFAULT_THEME, &ini, &fallback)
+	}
+}
+
+// Ids of every available theme, the built-in ones first and then any other theme files
+pub fn available() -> Vec<String> {
+	let mut ids: Vec<String> = BUILT_IN.iter().map(|(id, _)| id.to_string()).collect();
+
+	if let Ok(entries) = fs::read_dir(assets::path(THEME_DIR)) {
+		let mut files: Vec<String> = entries
+			.filter_map(|entry| entry.ok())
+			.map(|entry| entry.path())
+			.filter(|path| path.extension().is_some_and(|extension| extension == "ini"))
+			.filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+			.filter(|id| !ids.contains(id))
+			.collect();
+
+		files.sort();
+		ids.extend(files);
+	}
+
+	ids
+}
+
+// The theme after `id` in `available`, wrapping around
+pub fn next(id: &str) -> String {
+	let ids = available();
+	let current = ids.iter().position(|other| other == id).unwrap_or(0);
+
+	ids[(current + 1) % ids.len()].clone()
+}
+
+fn theme_ini(id: &str) -> Option<Ini> {
+	let path = assets::path(THEME_DIR).join(format!("{}.ini", id));
+
+	if path.is_file() {
+		return Some(Ini::load(path));
+	}
+
+	BUILT_IN.iter().find(|(built_in, _)| *built_in == id).map(|(_, source)| Ini::parse(source))
+}
+
+// Parse a color written as #rrggbb or #rrggbbaa
+fn parse_color(input: &str) -> Option<Rgba> {
+	let hex = input.strip_prefix('#')?;
+
+	if (hex.len() != 6 && hex.len() != 8) || !hex.is_ascii() {
+		return None;
+	}
+
+	let mut color = [1.0; 4];
+
+	for (i, channel) in color.iter_mut().enumerate().take(hex.len() / 2) {
+		*channel = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()? as f32 / 255.0;
+	}
+
+	Some(color)
+}
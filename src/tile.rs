@@ -1,16 +1,42 @@
 use piston_window::{Context, G2d};
 use graphics::*;
+use graphics::character::CharacterCache;
+
+use twenty::engine::EMPTY;
 
 use crate::playboard::PlayBoard;
-use crate::utils::{Vector, RenderContext};
+use crate::utils::{Vector, RenderContext, TILE_TEXTURE_SIZE};
+
+// Corner radius and the widest the number can be, relative to the tile
+const CORNER_RADIUS: f64 = 0.06;
+const TEXT_WIDTH: f64 = 0.8;
+
+// Font sizes of the numbers are rounded down to a multiple of this, so resizing the window only
+// rasterizes a few sizes of each digit instead of one for every pixel
+const FONT_SIZE_STEP: u32 = 4;
+
+// Seconds each animation takes at normal speed
+pub const SLIDE_TIME: f64 = 0.12;
+const POP_TIME: f64 = 0.15;
+const GROW_TIME: f64 = 0.15;
+
+// How much larger a merged tile gets at the height of its pop
+const POP_SIZE: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TileAnimation {
+	// Briefly grows and shrinks back after a merge
+	Pop,
+	// Grows from nothing after spawning
+	Grow
+}
 
 #[derive(Debug)]
 pub struct Tile {
 	position: Vector,
-	pub scale: f64,
 	pub value: usize,
-	pub occupied: usize,
-	pub taken: bool
+	// Running animation and how far along it is, from 0 to 1
+	animation: Option<(TileAnimation, f64)>
 }
 
 impl Tile {
@@ -18,10 +44,8 @@ impl Tile {
 	pub fn new(x: f64, y: f64, value: usize) -> Self {
 		Tile {
 			position: Vector::new(x, y),
-			scale: 1.0,
-			value: value,
-			occupied: value,
-			taken: false
+			value,
+			animation: None
 		}
 	}
 
@@ -29,109 +53,177 @@ impl Tile {
 	pub fn empty(x: f64, y: f64) -> Self {
 		Tile {
 			position: Vector::new(x, y),
-			scale: 1.0,
-			value: 99,
-			occupied: 99,
-			taken: false
+			value: EMPTY,
+			animation: None
 		}
 	}
 
-	pub fn render(&mut self, render_ctx: &mut RenderContext, ctx: Context, gl: &mut G2d) {
-		// Get transform for drawing
-		let transform = ctx.transform
-			.trans(self.position.x * PlayBoard::PADDED_TILE_SIZE, self.position.y * PlayBoard::PADDED_TILE_SIZE)
-			.trans(render_ctx.window_size[0] / 2.0, render_ctx.window_size[1] / 2.0)
-			.trans(render_ctx.board_size[0], render_ctx.board_size[1])
-			.scale(self.scale, self.scale)
-			.trans(-48.0, -48.0);
+	pub fn pop(&mut self) {
+		self.animation = Some((TileAnimation::Pop, 0.0));
+	}
 
+	pub fn grow(&mut self) {
+		self.animation = Some((TileAnimation::Grow, 0.0));
+	}
 
-		// If the tile's scale is more than 1, make it 1
-		if self.scale > 1.0 {
-			self.scale = (self.scale - 4.0 * render_ctx.dt).max(1.0);
-		}
+	pub fn stop_animation(&mut self) {
+		self.animation = None;
+	}
 
-		// Check if this tile is an empty one
-		if self.value == 99 {
-			// Draw empty tile
-			if let Some(texture) = &render_ctx.textures.empty_tile {
-				image(texture, transform, gl);
+	// Advance the running animation by `dt` seconds of normal speed
+	pub fn update(&mut self, dt: f64) {
+		if let Some((animation, progress)) = &mut self.animation {
+			let duration = match animation {
+				TileAnimation::Pop => POP_TIME,
+				TileAnimation::Grow => GROW_TIME
+			};
+
+			*progress += dt / duration;
+
+			if *progress >= 1.0 {
+				self.animation = None;
 			}
 		}
-		else {
-			// Draw tile
-			image(&render_ctx.textures.nums[self.value], transform, gl);
+	}
+
+	fn scale(&self) -> f64 {
+		match self.animation {
+			Some((TileAnimation::Pop, progress)) => 1.0 + POP_SIZE * (progress * std::f64::consts::PI).sin(),
+			Some((TileAnimation::Grow, progress)) => ease_out(progress),
+			None => 1.0
 		}
 	}
 
+	// Draw the empty cell under the tile
+	pub fn render_cell(&self, render_ctx: &mut RenderContext, ctx: Context, gl: &mut G2d) {
+		let center = tile_center(&self.position, render_ctx);
+		draw_tile(EMPTY, center, 1.0, render_ctx, ctx, gl);
+	}
+
+	pub fn render(&self, render_ctx: &mut RenderContext, ctx: Context, gl: &mut G2d) {
+		if self.value == EMPTY { return; }
+
+		let center = tile_center(&self.position, render_ctx);
+		draw_tile(self.value, center, self.scale(), render_ctx, ctx, gl);
+	}
+
 	pub fn reset(&mut self) {
-		self.taken = false;
-		self.occupied = 99;
-		self.value = 99;
+		self.value = EMPTY;
+		self.animation = None;
 	}
 }
 
 #[derive(Debug)]
 pub struct MovingTile {
-	pub position: Vector,
-	new_position: Vector,
+	from: Vector,
+	pub to: Vector,
 	pub value: usize,
-	pub combine: bool
+	pub combine: bool,
+	// How far the tile has slid, from 0 to 1
+	progress: f64
 }
 
 impl MovingTile {
 	pub fn new(x: f64, y: f64, new_x: f64, new_y: f64, value: usize, combine: bool) -> Self {
 		MovingTile {
-			position: Vector::new(x, y),
-			new_position: Vector::new(new_x, new_y),
-			value: value,
-			combine: combine
+			from: Vector::new(x, y),
+			to: Vector::new(new_x, new_y),
+			value,
+			combine,
+			progress: 0.0
 		}
 	}
 
-	pub fn render(&mut self, render_ctx: &mut RenderContext, ctx: Context, gl: &mut G2d) {
-		// Move the position of the tile to its new position
-		let move_dt = render_ctx.dt * 32.0;
+	// Advance the slide by `dt` seconds of normal speed
+	pub fn update(&mut self, dt: f64) {
+		self.progress = (self.progress + dt / SLIDE_TIME).min(1.0);
+	}
 
-		// Move X
-		if (self.position.x - self.new_position.x).abs() > move_dt {
-			if self.position.x < self.new_position.x {
-				self.position.x += move_dt;
-			}
-			else {
-				self.position.x -= move_dt;
-			}
-		}
-		else {
-			self.position.x = self.new_position.x;
-		}
+	pub fn render(&self, render_ctx: &mut RenderContext, ctx: Context, gl: &mut G2d) {
+		// Slide quickly at first and slow down towards the destination
+		let t = ease_out(self.progress);
+		let position = Vector::new(
+			self.from.x + (self.to.x - self.from.x) * t,
+			self.from.y + (self.to.y - self.from.y) * t
+		);
 
-		// Move Y
-		if (self.position.y - self.new_position.y).abs() > move_dt {
-			if self.position.y < self.new_position.y {
-				self.position.y += move_dt;
-			}
-			else {
-				self.position.y -= move_dt;
-			}
-		}
-		else {
-			self.position.y = self.new_position.y;
-		}
+		let center = tile_center(&position, render_ctx);
+		draw_tile(self.value, center, 1.0, render_ctx, ctx, gl);
+	}
 
-		// Get transform for drawing
-		let transform = ctx.transform
-			.trans(self.position.x * PlayBoard::PADDED_TILE_SIZE, self.position.y * PlayBoard::PADDED_TILE_SIZE)
-			.trans(render_ctx.window_size[0] / 2.0, render_ctx.window_size[1] / 2.0)
-			.trans(render_ctx.board_size[0], render_ctx.board_size[1])
-			.trans(-48.0, -48.0);
+	pub fn is_finished(&self) -> bool {
+		self.progress >= 1.0
+	}
+}
+
+// Cubic ease out, starting fast and slowing down to a stop at 1
+fn ease_out(t: f64) -> f64 {
+	1.0 - (1.0 - t.clamp(0.0, 1.0)).powi(3)
+}
+
+// Number shown on a tile, as a power of 2 once it is too large to write out
+fn tile_label(value: usize) -> String {
+	2u128.checked_pow(value as u32 + 1).map_or(format!("2^{}", value + 1), |number| number.to_string())
+}
+
+// Center of a tile at a board position, in pixels
+fn tile_center(position: &Vector, render_ctx: &RenderContext) -> [f64; 2] {
+	[
+		render_ctx.window_size[0] / 2.0 + render_ctx.board_offset[0] + position.x * render_ctx.tile_size,
+		render_ctx.window_size[1] / 2.0 + render_ctx.board_offset[1] + position.y * render_ctx.tile_size
+	]
+}
 
+// Draw a tile centered on a point. Tiles with an image in the assets use it, any other tile is
+// drawn as a rounded square with its number.
+fn draw_tile(value: usize, [x, y]: [f64; 2], scale: f64, render_ctx: &mut RenderContext, ctx: Context, gl: &mut G2d) {
+	let texture = if value == EMPTY { render_ctx.textures.empty_tile.as_ref() } else { render_ctx.textures.tile(value) };
 
-		// Draw tile
-		image(&render_ctx.textures.nums[self.value], transform, gl);
+	if let Some(texture) = texture {
+		let scale = scale * render_ctx.tile_scale();
+		let transform = ctx.transform
+			.trans(x, y)
+			.scale(scale, scale)
+			.trans(-TILE_TEXTURE_SIZE / 2.0, -TILE_TEXTURE_SIZE / 2.0);
+
+		image(texture, transform, gl);
+		return;
 	}
 
-	pub fn is_finished(&self) -> bool {
-		self.position.x == self.new_position.x && self.position.y == self.new_position.y
+	// Same size as the tile images, leaving the padding between tiles
+	let unscaled_size = render_ctx.tile_size * TILE_TEXTURE_SIZE / PlayBoard::PADDED_TILE_SIZE;
+	let size = unscaled_size * scale;
+	let theme = &render_ctx.theme;
+	let color = if value == EMPTY { theme.empty_tile } else { theme.tile_color(value) };
+
+	Rectangle::new_round(color, size * CORNER_RADIUS)
+		.draw([x - size / 2.0, y - size / 2.0, size, size], &ctx.draw_state, ctx.transform, gl);
+
+	if value == EMPTY { return; }
+
+	let label = tile_label(value);
+	let text_color = theme.tile_text(value);
+	let glyphs = &mut render_ctx.glyphs.text;
+
+	// Shrink the font until the number fits on the tile. The size is worked out without the pop and
+	// grow animations, which scale the number like the tile instead, since the glyph cache keeps
+	// every size it has drawn.
+	let mut font_size = (unscaled_size * 0.5) as u32;
+	let width = glyphs.width(font_size, &label).unwrap_or(0.0);
+
+	if width > unscaled_size * TEXT_WIDTH {
+		font_size = (font_size as f64 * unscaled_size * TEXT_WIDTH / width) as u32;
 	}
-}
\ No newline at end of file
+
+	let font_size = if font_size > FONT_SIZE_STEP { font_size - font_size % FONT_SIZE_STEP } else { font_size.max(1) };
+	let width = glyphs.width(font_size, &label).unwrap_or(0.0);
+
+	// Text is drawn from its baseline, which sits about a third of the font size below the center
+	let transform = ctx.transform
+		.trans(x, y)
+		.scale(scale, scale)
+		.trans(-width / 2.0, font_size as f64 * 0.35);
+
+	text(text_color, font_size, &label, glyphs, transform, gl)
+		.expect("Failed to draw text");
+}
@@ -0,0 +1,308 @@
+use crossterm::{
+	cursor::{Hide, MoveTo, Show},
+	event::{self, Event, KeyCode, KeyEventKind},
+	execute, queue,
+	style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+	terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen}
+};
+use std::{cmp, io::{self, Write}};
+
+use twenty::{
+	engine::{Board, Direction, EMPTY},
+	history::History,
+	replay::Replay
+};
+
+use crate::{
+	options::Options,
+	playboard::BoardEvent,
+	user_data::{self, UserData}
+};
+
+// Size of a single cell in characters, not including the gap between cells
+const CELL_WIDTH: u16 = 8;
+const CELL_HEIGHT: u16 = 3;
+
+const BOARD_COLOR: Color = Color::Rgb { r: 187, g: 173, b: 160 };
+const EMPTY_COLOR: Color = Color::Rgb { r: 205, g: 193, b: 180 };
+const DARK_TEXT: Color = Color::Rgb { r: 119, g: 110, b: 101 };
+const LIGHT_TEXT: Color = Color::Rgb { r: 249, g: 246, b: 242 };
+
+// Background colors of the tiles from 2 to 2048, higher tiles use the last color
+const TILE_COLORS: [(u8, u8, u8); 12] = [
+	(238, 228, 218),
+	(237, 224, 200),
+	(242, 177, 121),
+	(245, 149, 99),
+	(246, 124, 95),
+	(246, 94, 59),
+	(237, 207, 114),
+	(237, 204, 97),
+	(237, 200, 80),
+	(237, 197, 63),
+	(237, 194, 46),
+	(60, 58, 50)
+];
+
+#[derive(PartialEq)]
+enum TuiState {
+	Playing,
+	Won,
+	GameOver
+}
+
+// Terminal frontend playing by the same rules and user data as the window
+struct Tui {
+	board: Board,
+	history: History,
+	replay: Replay,
+	user_data: UserData,
+	seed: Option<u64>,
+	state: TuiState,
+	// Whether the win has been counted this game, undoing the winning move and making it again
+	// doesn't count it twice
+	win_counted: bool,
+	quit: bool
+}
+
+// Play the game in the terminal until the player quits
+pub fn run(options: Options) -> io::Result<()> {
+	let mut tui = Tui::new(options);
+	let mut stdout = io::stdout();
+
+	terminal::enable_raw_mode()?;
+	execute!(stdout, EnterAlternateScreen, Hide)?;
+
+	let res = tui.event_loop(&mut stdout);
+
+	// Always give the terminal back, even if drawing failed
+	execute!(stdout, ResetColor, Show, LeaveAlternateScreen)?;
+	terminal::disable_raw_mode()?;
+
+	// Save the game so it can be resumed next time, finished games are not resumed
+	user_data::save_unfinished_replay(&tui.replay);
+
+	if tui.state == TuiState::GameOver {
+		user_data::save_game(None);
+	}
+	else {
+		user_data::save_game(Some(&tui.board));
+	}
+
+	res
+}
+
+impl Tui {
+	fn new(options: Options) -> Self {
+		let user_data = UserData::load();
+		let board = user_data.start_game(&options);
+
+		let mut tui = Tui {
+			replay: user_data::resume_replay(&board),
+			board,
+			history: History::new(user_data.history_depth()),
+			user_data,
+			seed: options.seed,
+			state: TuiState::Playing,
+			win_counted: false,
+			quit: false
+		};
+
+		tui.update_state();
+		tui
+	}
+
+	fn event_loop(&mut self, out: &mut impl Write) -> io::Result<()> {
+		while !self.quit {
+			self.draw(out)?;
+
+			match event::read()? {
+				Event::Key(key) if key.kind == KeyEventKind::Press => self.key_press(key.code),
+				_ => ()
+			}
+		}
+
+		Ok(())
+	}
+
+	fn key_press(&mut self, key: KeyCode) {
+		match key {
+			KeyCode::Left | KeyCode::Char('a') => self.slide(Direction::Left),
+			KeyCode::Right | KeyCode::Char('d') => self.slide(Direction::Right),
+			KeyCode::Up | KeyCode::Char('w') => self.slide(Direction::Up),
+			KeyCode::Down | KeyCode::Char('s') => self.slide(Direction::Down),
+			KeyCode::Char('z') | KeyCode::Backspace => {
+				if let Some(previous) = self.history.undo(self.board.clone()) {
+					self.board = previous;
+					self.replay.undo();
+					self.update_state();
+				}
+			},
+			KeyCode::Char('y') => {
+				if let Some(next) = self.history.redo(self.board.clone()) {
+					self.board = next;
+					self.replay.redo();
+					self.update_state();
+				}
+			},
+			KeyCode::Enter if self.state == TuiState::Won => {
+				self.board.keep_playing();
+				self.record(BoardEvent::Continued);
+				self.state = TuiState::Playing;
+				self.check_end();
+			},
+			KeyCode::Char(' ') => {
+				// Finished games were recorded when they ended
+				if self.state != TuiState::GameOver {
+					user_data::save_replay(&self.replay);
+				}
+
+				match self.seed {
+					Some(seed) => self.board.reset_with_seed(seed),
+					None => self.board.reset()
+				};
+
+				self.history.clear();
+				self.replay = Replay::new(&self.board);
+				self.state = TuiState::Playing;
+				self.win_counted = false;
+			},
+			KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+			_ => ()
+		}
+	}
+
+	fn slide(&mut self, direction: Direction) {
+		if self.state != TuiState::Playing { return; }
+
+		let before = self.board.clone();
+		let outcome = self.board.play(direction);
+
+		if outcome.moved() {
+			self.history.record(before);
+			self.replay.record(direction, outcome.spawned);
+
+			// Save the high score as soon as it is beaten, so it is kept if the game is quit halfway
+			if self.board.score() > self.user_data.highscore() {
+				self.user_data.set_highscore(self.board.score());
+				self.user_data.save();
+			}
+
+			self.check_end();
+		}
+	}
+
+	// Set the state from the board without recording any events, used after undo and redo
+	fn update_state(&mut self) {
+		self.state =
+			if self.board.has_won() { TuiState::Won }
+			else if self.board.is_game_over() { TuiState::GameOver }
+			else { TuiState::Playing };
+	}
+
+	// Check if the game has been won or is over
+	fn check_end(&mut self) {
+		if self.board.has_won() {
+			self.state = TuiState::Won;
+
+			if !self.win_counted {
+				self.record(BoardEvent::Won);
+				self.win_counted = true;
+			}
+		}
+		else if self.board.is_game_over() {
+			self.state = TuiState::GameOver;
+			self.record(BoardEvent::GameOver);
+			user_data::save_replay(&self.replay);
+		}
+	}
+
+	fn record(&mut self, event: BoardEvent) {
+		self.user_data.record(event);
+		self.user_data.save();
+	}
+
+	fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+		queue!(out, ResetColor, Clear(ClearType::All), MoveTo(2, 1))?;
+
+		// Title, score and high score
+		queue!(
+			out,
+			Print(format!(
+				"2048    Score: {}    High score: {}",
+				self.board.score(),
+				cmp::max(self.board.score(), self.user_data.highscore())
+			))
+		)?;
+
+		let width = self.board.width() as u16;
+		let height = self.board.height() as u16;
+		let (left, top) = (2, 3);
+
+		// Board background with a 1 character gap around every cell
+		queue!(out, SetBackgroundColor(BOARD_COLOR))?;
+		for row in 0..height * (CELL_HEIGHT + 1) + 1 {
+			queue!(out, MoveTo(left, top + row), Print(" ".repeat((width * (CELL_WIDTH + 2) + 2) as usize)))?;
+		}
+
+		// Cells
+		for y in 0..height {
+			for x in 0..width {
+				let value = self.board.get(x as usize, y as usize);
+				let (background, foreground) = tile_colors(value);
+				let label = if value == EMPTY { String::new() } else { 2u64.pow(value as u32 + 1).to_string() };
+
+				let cell_left = left + 2 + x * (CELL_WIDTH + 2);
+				let cell_top = top + 1 + y * (CELL_HEIGHT + 1);
+
+				queue!(out, SetBackgroundColor(background), SetForegroundColor(foreground))?;
+
+				for row in 0..CELL_HEIGHT {
+					let line = if row == CELL_HEIGHT / 2 {
+						format!("{:^width$}", label, width = CELL_WIDTH as usize)
+					}
+					else {
+						" ".repeat(CELL_WIDTH as usize)
+					};
+
+					queue!(out, MoveTo(cell_left, cell_top + row), Print(line))?;
+				}
+			}
+		}
+
+		queue!(out, ResetColor)?;
+
+		// Messages and controls below the board
+		let below = top + height * (CELL_HEIGHT + 1) + 2;
+		let message = match self.state {
+			TuiState::Playing => String::new(),
+			TuiState::Won => "You win! Keep playing with ENTER, retry with SPACE".to_string(),
+			TuiState::GameOver => format!("Game over! Score: {}. Retry with SPACE", self.board.score())
+		};
+		let undo = if self.history.is_enabled() { "Z/Y undo/redo, " } else { "Ranked game, no undo, " };
+
+		queue!(
+			out,
+			MoveTo(2, below),
+			Print(message),
+			MoveTo(2, below + 1),
+			Print(format!("Arrows/WASD slide, {}SPACE restart, Q quit", undo)),
+			MoveTo(2, below + 2),
+			Print(format!("Seed: {}", self.board.seed()))
+		)?;
+
+		out.flush()
+	}
+}
+
+// Background and text color of a cell
+fn tile_colors(value: usize) -> (Color, Color) {
+	if value == EMPTY {
+		return (EMPTY_COLOR, DARK_TEXT);
+	}
+
+	let (r, g, b) = TILE_COLORS[cmp::min(value, TILE_COLORS.len() - 1)];
+	let foreground = if value < 2 { DARK_TEXT } else { LIGHT_TEXT };
+
+	(Color::Rgb { r, g, b }, foreground)
+}
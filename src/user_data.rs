@@ -0,0 +1,333 @@
+use std::{fs, path::{Path, PathBuf}, sync::Arc, time::{SystemTime, UNIX_EPOCH}};
+
+use twenty::{
+	engine::{Board, DEFAULT_TARGET},
+	ini::Ini,
+	replay::{self, Replay},
+	save,
+	spawn::WeightedSpawn,
+	strategy
+};
+
+use crate::{
+	assets,
+	bindings::{self, Action, Bindings},
+	options::{Options, parse_board_size},
+	playboard::BoardEvent,
+	theme
+};
+
+// Files in the asset directory
+const DATA_FILE: &str = "data.ini";
+const SAVE_FILE: &str = "save.ini";
+const REPLAY_FILE: &str = "replay.ini";
+// Directory in the asset directory finished games are recorded in
+const REPLAY_DIR: &str = "replays";
+
+const SECTION: &str = "UserData";
+const SETTINGS_SECTION: &str = "Settings";
+const STATISTICS_SECTION: &str = "Statistics";
+const CONTROLS_SECTION: &str = "Controls";
+
+const DEFAULT_UNDO_DEPTH: usize = 32;
+const DEFAULT_INPUT_QUEUE: usize = 2;
+const DEFAULT_BOARD_SIZE: (usize, usize) = (4, 4);
+const DEFAULT_AI_DEPTH: usize = 2;
+// Deeper searches take minutes per move on large boards
+const MIN_AI_DEPTH: usize = 1;
+const MAX_AI_DEPTH: usize = 4;
+const DEFAULT_AUTOPLAY_RATE: f64 = 4.0;
+const DEFAULT_AUTOPLAY_STRATEGY: &str = "expectimax";
+const DEFAULT_GAMEPAD_DEAD_ZONE: f64 = 0.5;
+const DEFAULT_ANIMATION_SPEED: f64 = 1.0;
+
+// Persistent user data stored in `data.ini` in the asset directory
+pub struct UserData {
+	ini: Ini,
+	path: PathBuf
+}
+
+impl UserData {
+	pub fn load() -> Self {
+		let path = assets::path(DATA_FILE);
+
+		UserData {
+			ini: Ini::load(&path),
+			path
+		}
+	}
+
+	pub fn highscore(&self) -> u32 {
+		self.ini.get_parsed(SECTION, "highscore").unwrap_or(0)
+	}
+
+	pub fn set_highscore(&mut self, highscore: u32) {
+		self.ini.set(SECTION, "highscore", highscore);
+	}
+
+	// Width and height of new games
+	pub fn board_size(&self) -> (usize, usize) {
+		self.ini.get(SETTINGS_SECTION, "board_size")
+			.and_then(parse_board_size)
+			.unwrap_or(DEFAULT_BOARD_SIZE)
+	}
+
+	pub fn set_board_size(&mut self, (width, height): (usize, usize)) {
+		self.ini.set(SETTINGS_SECTION, "board_size", format!("{}x{}", width, height));
+	}
+
+	// How many moves can be undone
+	pub fn undo_depth(&self) -> usize {
+		self.ini.get_parsed(SETTINGS_SECTION, "undo_depth").unwrap_or(DEFAULT_UNDO_DEPTH)
+	}
+
+	// How many moves made while tiles are moving are played once they stop
+	pub fn input_queue(&self) -> usize {
+		self.ini.get_parsed(SETTINGS_SECTION, "input_queue").unwrap_or(DEFAULT_INPUT_QUEUE)
+	}
+
+	// Whether a move made while tiles are moving ends the animation right away instead of being queued
+	pub fn finish_animation_on_input(&self) -> bool {
+		self.ini.get_parsed(SETTINGS_SECTION, "finish_animation_on_input").unwrap_or(false)
+	}
+
+	// Multiplier of the speed of tile animations, 0 turns them off
+	pub fn animation_speed(&self) -> f64 {
+		self.ini.get_parsed(SETTINGS_SECTION, "animation_speed")
+			.filter(|&speed: &f64| speed >= 0.0 && speed.is_finite())
+			.unwrap_or(DEFAULT_ANIMATION_SPEED)
+	}
+
+	// How far a gamepad stick has to be pushed to slide the board, from 0 to 1
+	pub fn gamepad_dead_zone(&self) -> f64 {
+		self.ini.get_parsed(SETTINGS_SECTION, "gamepad_dead_zone")
+			.filter(|&dead_zone: &f64| dead_zone > 0.0 && dead_zone < 1.0)
+			.unwrap_or(DEFAULT_GAMEPAD_DEAD_ZONE)
+	}
+
+	// Ranked games cannot undo moves so that their scores stay comparable
+	pub fn ranked(&self) -> bool {
+		self.ini.get_parsed(SETTINGS_SECTION, "ranked").unwrap_or(false)
+	}
+
+	// How many moves can be undone in this game, taking ranked games into account
+	pub fn history_depth(&self) -> usize {
+		if self.ranked() { 0 } else { self.undo_depth() }
+	}
+
+	// Spawn weights as `value:weight` pairs, such as "2:9, 4:1". Falls back to the classic rules.
+	pub fn spawn_policy(&self) -> WeightedSpawn {
+		match self.ini.get(SETTINGS_SECTION, "spawn_weights") {
+			Some(weights) => WeightedSpawn::parse(weights).unwrap_or_else(|err| {
+				println!("Invalid spawn_weights `{}` in {}: {}, using the classic rules", weights, self.path.display(), err);
+				WeightedSpawn::classic()
+			}),
+			None => WeightedSpawn::classic()
+		}
+	}
+
+	// Exponent of the tile that wins the game, set as a tile value such as 2048
+	pub fn target(&self) -> usize {
+		match self.ini.get_parsed::<u32>(SETTINGS_SECTION, "target_tile") {
+			Some(value) if value >= 4 && value.is_power_of_two() => value.trailing_zeros() as usize - 1,
+			_ => DEFAULT_TARGET
+		}
+	}
+
+	// Number of moves the AI looks ahead, values out of range are clamped
+	pub fn ai_depth(&self) -> usize {
+		self.ini.get_parsed::<usize>(SETTINGS_SECTION, "ai_depth")
+			.map_or(DEFAULT_AI_DEPTH, |depth| depth.clamp(MIN_AI_DEPTH, MAX_AI_DEPTH))
+	}
+
+	// Moves per second made by autoplay
+	pub fn autoplay_rate(&self) -> f64 {
+		self.ini.get_parsed(SETTINGS_SECTION, "autoplay_rate")
+			.filter(|&rate: &f64| rate > 0.0)
+			.unwrap_or(DEFAULT_AUTOPLAY_RATE)
+	}
+
+	// Name of the strategy that plays with autoplay, one of `strategy::NAMES`
+	pub fn autoplay_strategy(&self) -> &str {
+		self.ini.get(SETTINGS_SECTION, "autoplay_strategy")
+			.filter(|name| strategy::NAMES.contains(name))
+			.unwrap_or(DEFAULT_AUTOPLAY_STRATEGY)
+	}
+
+	pub fn set_autoplay_strategy(&mut self, name: &str) {
+		self.ini.set(SETTINGS_SECTION, "autoplay_strategy", name);
+	}
+
+	// Id of the theme the window is drawn with
+	pub fn theme(&self) -> &str {
+		self.ini.get(SETTINGS_SECTION, "theme").unwrap_or(theme::DEFAULT_THEME)
+	}
+
+	pub fn set_theme(&mut self, id: &str) {
+		self.ini.set(SETTINGS_SECTION, "theme", id);
+	}
+
+	// Keys bound to every action, actions missing from the file keep their default keys
+	pub fn bindings(&self) -> Bindings {
+		let mut bindings = Bindings::default();
+
+		for &action in &Action::ALL {
+			if let Some(input) = self.ini.get(CONTROLS_SECTION, action.name()) {
+				match bindings::parse_keys(input) {
+					Some(keys) => bindings.set(action, keys),
+					None => println!("Invalid keys `{}` for {} in {}, using the defaults", input, action.name(), self.path.display())
+				}
+			}
+		}
+
+		bindings
+	}
+
+	pub fn set_bindings(&mut self, bindings: &Bindings) {
+		for &action in &Action::ALL {
+			self.ini.set(CONTROLS_SECTION, action.name(), bindings.key_names(action));
+		}
+	}
+
+	// Apply the user's spawn and win rules to a board
+	pub fn apply_rules(&self, board: &mut Board) {
+		board.set_spawn_policy(Arc::new(self.spawn_policy()));
+		board.set_target(self.target());
+	}
+
+	// Resume the last game or start a new one. Choosing a seed or size on the command line always
+	// starts a new game.
+	pub fn start_game(&self, options: &Options) -> Board {
+		let saved = if options.seed.is_none() && options.size.is_none() { load_game() } else { None };
+
+		match saved {
+			Some(mut saved) => {
+				self.apply_rules(&mut saved);
+				saved
+			},
+			None => self.new_game(options.size.unwrap_or_else(|| self.board_size()), options.seed)
+		}
+	}
+
+	// Start a new game with 1 tile on it
+	pub fn new_game(&self, (width, height): (usize, usize), seed: Option<u64>) -> Board {
+		let mut board = match seed {
+			Some(seed) => Board::with_seed(width, height, seed),
+			None => Board::new(width, height)
+		};
+
+		self.apply_rules(&mut board);
+		board.spawn_tile();
+		board
+	}
+
+	// Count a game event in the statistics
+	pub fn record(&mut self, event: BoardEvent) {
+		let key = match event {
+			BoardEvent::Won => "wins",
+			BoardEvent::Continued => "continues",
+			BoardEvent::GameOver => "games_over"
+		};
+
+		let count: u32 = self.ini.get_parsed(STATISTICS_SECTION, key).unwrap_or(0);
+		self.ini.set(STATISTICS_SECTION, key, count + 1);
+	}
+
+	// Write the user data back to disk, failing to do so is not fatal
+	pub fn save(&self) {
+		if let Err(err) = self.ini.save(&self.path) {
+			println!("Failed to save user data to {}: {}", self.path.display(), err);
+		}
+	}
+}
+
+// Load the game saved by the last run, if there is one
+pub fn load_game() -> Option<Board> {
+	let path = assets::path(SAVE_FILE);
+
+	if !path.exists() {
+		return None;
+	}
+
+	match save::read(&Ini::load(&path)) {
+		Ok(board) => Some(board),
+		Err(err) => {
+			println!("Could not resume the saved game in {}: {}. Starting a new game.", path.display(), err);
+			None
+		}
+	}
+}
+
+// Save a game in progress so it can be resumed on the next run, or remove the old save if there is
+// nothing to resume
+pub fn save_game(board: Option<&Board>) {
+	let path = assets::path(SAVE_FILE);
+
+	let res = match board {
+		Some(board) => save::write(board).save(&path),
+		None if path.exists() => fs::remove_file(&path),
+		None => Ok(())
+	};
+
+	if let Err(err) = res {
+		println!("Failed to save game to {}: {}", path.display(), err);
+	}
+}
+
+// Load a replay file, such as one shared by another player
+pub fn load_replay(path: &Path) -> Result<Replay, String> {
+	if !path.exists() {
+		return Err(format!("{} does not exist", path.display()));
+	}
+
+	replay::read(&Ini::load(path)).map_err(|err| format!("{} is not a valid replay: {}", path.display(), err))
+}
+
+// Continue recording the last game if it leads to the given board, otherwise start a new recording
+pub fn resume_replay(board: &Board) -> Replay {
+	load_replay(&assets::path(REPLAY_FILE))
+		.ok()
+		.filter(|replay| replay.leads_to(board))
+		.unwrap_or_else(|| Replay::new(board))
+}
+
+// Save the recording of the game in progress, so it can be continued when the game is resumed
+pub fn save_unfinished_replay(replay: &Replay) {
+	if replay.is_empty() { return; }
+
+	let path = assets::path(REPLAY_FILE);
+
+	if let Err(err) = replay::write(replay).save(&path) {
+		println!("Failed to save replay to {}: {}", path.display(), err);
+	}
+}
+
+// Keep the recording of a finished game in its own file, named after its seed and the time it
+// finished. Games without any moves are not worth keeping.
+pub fn save_replay(replay: &Replay) {
+	if replay.is_empty() { return; }
+
+	let directory = assets::path(REPLAY_DIR);
+
+	if let Err(err) = fs::create_dir_all(&directory) {
+		println!("Failed to create {}: {}", directory.display(), err);
+		return;
+	}
+
+	let name = format!(
+		"{}-{}",
+		replay.initial().seed(),
+		SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs())
+	);
+
+	// Games finished within the same second get a number
+	let path = (1..)
+		.map(|i| if i == 1 { format!("{}.ini", name) } else { format!("{}-{}.ini", name, i) })
+		.map(|file| directory.join(file))
+		.find(|path| !path.exists())
+		.expect("there are always more numbers");
+
+	if let Err(err) = replay::write(replay).save(&path) {
+		println!("Failed to save replay to {}: {}", path.display(), err);
+	}
+}
@@ -1,76 +1,46 @@
-use piston_window::{PistonWindow, Glyphs, TextureSettings, G2dTexture, Texture, Flip, G2d};
+use piston_window::{PistonWindow, Context, Glyphs, TextureSettings, G2dTexture, Texture, Flip, G2d};
 use graphics::*;
+use std::{cmp, process};
 
-pub const FRAME_COUNT_FOR_AVG: usize = 60;
+use crate::assets;
+use crate::playboard::PlayBoard;
+use crate::theme::Theme;
+
+// Size the layout is designed for. It is scaled to fit the window, keeping its aspect ratio.
+pub const LAYOUT_SIZE: [f64; 2] = [700.0, 500.0];
+// Smallest scale the layout is drawn at, smaller windows cut it off instead of making text unreadable
+const MIN_LAYOUT_SCALE: f64 = 0.6;
+
+// Space available for the board, and the position of its center relative to the layout's center
+const BOARD_SPACE: f64 = 440.0;
+const BOARD_CENTER: [f64; 2] = [105.0, 1.0];
+
+// Width and height of the tile textures
+pub const TILE_TEXTURE_SIZE: f64 = 96.0;
 
 pub type Rgba = [f32; 4];
 
+// Number of tile images looked for in the assets, from 2 to 131072
+const SKIN_TILES: usize = 17;
+
+// Optional tile images drawn instead of the generated tiles. Any of them can be missing.
 pub struct TextureStorage {
-	pub nums: Vec<G2dTexture>,
+	// Image of every tile value, indexed by its exponent
+	pub nums: Vec<Option<G2dTexture>>,
 	pub empty_tile: Option<G2dTexture>
 }
 
 impl TextureStorage {
 	fn new() -> Self {
 		TextureStorage {
-			nums: Vec::with_capacity(8),
+			nums: Vec::with_capacity(SKIN_TILES),
 			empty_tile: None
 		}
 	}
-}
-
-pub enum Direction {
-	Left,
-	Right,
-	Up,
-	Down
-}
-
-impl Direction {
-	pub fn displacement(&self) -> (isize, isize) {
-		match self {
-			Direction::Left => (-1, 0),
-			Direction::Right => (1, 0),
-			Direction::Up => (0, -1),
-			Direction::Down => (0, 1)
-		}
-	}
-
-	pub fn is_negative(&self) -> bool {
-		match self {
-			Direction::Right | Direction::Down => false,
-			Direction::Left | Direction::Up => true
-		}
-	}
-}
-
-pub struct ToggleKey {
-	pub pressed: bool,
-	pub released: bool
-}
-impl ToggleKey {
-	fn new() -> Self {
-		ToggleKey {
-			pressed: false,
-			released: true
-		}
-	}
-}
 
-pub struct KeyMap {
-	pub left: ToggleKey,
-	pub right: ToggleKey,
-	pub up: ToggleKey,
-	pub down: ToggleKey
-}
-impl KeyMap {
-	pub fn new() -> Self {
-		KeyMap {
-			left: ToggleKey::new(),
-			right: ToggleKey::new(),
-			up: ToggleKey::new(),
-			down: ToggleKey::new()
-		}
+	// Image of a tile value, if there is one
+	pub fn tile(&self, value: usize) -> Option<&G2dTexture> {
+		self.nums.get(value).and_then(Option::as_ref)
 	}
 }
 
@@ -82,63 +52,198 @@ pub struct Vector {
 impl Vector {
 	pub fn new(x: f64, y: f64) -> Self {
 		Vector {
-			x: x,
-			y: y
+			x,
+			y
 		}
 	}
 }
 
 pub struct GlyphsStorage {
-	pub fira_code_reg: Glyphs,
-	pub brandon_blk: Glyphs
+	// Font of all text but the seed
+	pub text: Glyphs,
+	pub mono: Glyphs
 }
 
 pub struct RenderContext {
 	pub window_size: [f64; 2],
-	pub board_size: [f64; 2],
+	// Scale of the layout and the window position of its top left corner
+	pub layout_scale: f64,
+	pub layout_origin: [f64; 2],
+	// Offset of the top left tile from the window's center, and the size of a tile, in pixels
+	pub board_offset: [f64; 2],
+	pub tile_size: f64,
 	pub glyphs: GlyphsStorage,
-	pub dt: f64,
-	pub avg: Vec<f64>,
-	pub textures: TextureStorage
+	pub textures: TextureStorage,
+	pub theme: Theme
 }
 
 impl RenderContext {
-	pub fn new(window: &mut PistonWindow, glyphs: GlyphsStorage) -> Self {
+	pub fn new(window: &mut PistonWindow, theme: Theme) -> Self {
 		RenderContext {
 			window_size: [0.0, 0.0],
-			board_size: [0.0, 0.0],
-			glyphs: glyphs,
-			dt: 0.0,
-			avg: Vec::with_capacity(FRAME_COUNT_FOR_AVG),
-			textures: RenderContext::create_textures(window)
+			layout_scale: 1.0,
+			layout_origin: [0.0, 0.0],
+			board_offset: [0.0, 0.0],
+			tile_size: PlayBoard::PADDED_TILE_SIZE,
+			glyphs: RenderContext::load_glyphs(window, &theme),
+			textures: RenderContext::create_textures(window, &theme),
+			theme
 		}
 	}
 
-	fn create_textures(window: &mut PistonWindow) -> TextureStorage {
+	// Switch to another theme, loading its fonts and tile images
+	pub fn set_theme(&mut self, window: &mut PistonWindow, theme: Theme) {
+		self.glyphs = RenderContext::load_glyphs(window, &theme);
+		self.textures = RenderContext::create_textures(window, &theme);
+		self.theme = theme;
+	}
+
+	// Scale the layout to the largest size that fits the window and center it
+	pub fn set_window_size(&mut self, window_size: [f64; 2]) {
+		self.window_size = window_size;
+		self.layout_scale = (window_size[0] / LAYOUT_SIZE[0])
+			.min(window_size[1] / LAYOUT_SIZE[1])
+			.max(MIN_LAYOUT_SCALE);
+		self.layout_origin = [
+			(window_size[0] - LAYOUT_SIZE[0] * self.layout_scale) / 2.0,
+			(window_size[1] - LAYOUT_SIZE[1] * self.layout_scale) / 2.0
+		];
+	}
+
+	// Transform for drawing in layout coordinates
+	pub fn layout_transform(&self, ctx: Context) -> [[f64; 3]; 2] {
+		ctx.transform
+			.trans(self.layout_origin[0], self.layout_origin[1])
+			.scale(self.layout_scale, self.layout_scale)
+	}
+
+	// Fit a board of the given width and height in the board space. Tiles shrink for larger boards but
+	// are never larger than a 4x4 board's tiles in the layout.
+	pub fn set_board_size(&mut self, (width, height): (usize, usize)) {
+		let tile_size = (BOARD_SPACE / cmp::max(width, height) as f64).min(PlayBoard::PADDED_TILE_SIZE);
+		self.tile_size = tile_size * self.layout_scale;
+
+		// Offset of the center of the top left tile
+		self.board_offset = [
+			BOARD_CENTER[0] * self.layout_scale - (width as f64 - 1.0) / 2.0 * self.tile_size,
+			BOARD_CENTER[1] * self.layout_scale - (height as f64 - 1.0) / 2.0 * self.tile_size
+		];
+	}
+
+	// Rectangle covering every tile of a board of the given width and height
+	pub fn board_rect(&self, (width, height): (usize, usize)) -> [f64; 4] {
+		[
+			self.window_size[0] / 2.0 + self.board_offset[0] - self.tile_size / 2.0,
+			self.window_size[1] / 2.0 + self.board_offset[1] - self.tile_size / 2.0,
+			width as f64 * self.tile_size,
+			height as f64 * self.tile_size
+		]
+	}
+
+	// Scale to draw tile textures at
+	pub fn tile_scale(&self) -> f64 {
+		self.tile_size / PlayBoard::PADDED_TILE_SIZE
+	}
+
+	fn load_glyphs(window: &mut PistonWindow, theme: &Theme) -> GlyphsStorage {
+		let defaults = Theme::default();
+
+		GlyphsStorage {
+			text: RenderContext::load_font(window, &theme.font, &defaults.font),
+			mono: RenderContext::load_font(window, &theme.mono_font, &defaults.mono_font)
+		}
+	}
+
+	// Load a font of the theme, falling back to the default theme's font from its file or built into
+	// the binary. Exits if neither can be loaded, there is no way to draw text without it.
+	fn load_font(window: &mut PistonWindow, font: &str, default: &str) -> Glyphs {
+		let files = if font == default { vec![font] } else { vec![font, default] };
+
+		for file in files {
+			let path = assets::path(file);
+
+			match window.load_font(&path) {
+				Ok(glyphs) => return glyphs,
+				Err(err) => println!("Could not load font {}: {}", path.display(), err)
+			}
+		}
+
+		if let Some(bytes) = assets::embedded_font(default) {
+			println!("Using the built-in {} font", default);
+
+			return Glyphs::from_bytes(bytes, window.create_texture_context(), TextureSettings::new())
+				.expect("Built-in fonts are valid");
+		}
+
+		println!("No font could be loaded. Set the asset directory with --assets <path> or {}.", assets::ASSETS_VAR);
+		process::exit(1);
+	}
+
+	fn create_textures(window: &mut PistonWindow, theme: &Theme) -> TextureStorage {
 		let mut texture_storage = TextureStorage::new();
 
+		// Themes without tile images draw every tile
+		let directory = match &theme.tile_images {
+			Some(directory) => assets::path(directory),
+			None => return texture_storage
+		};
+
 		// Create texture making tools
 		let mut ctx = window.create_texture_context();
 		let settings = TextureSettings::new();
 
-		// Load each tile texture
-		for i in 1..=17 {
-			if let Ok(texture) = Texture::from_path(&mut ctx, format!("./assets/t{:?}.png", 2i32.pow(i)), Flip::None, &settings) {
-				texture_storage.nums.push(texture);
+		// Images that could not be loaded, those tiles are drawn instead
+		let mut missing = Vec::new();
+
+		let mut load = |file: String| {
+			let texture = Texture::from_path(&mut ctx, directory.join(&file), Flip::None, &settings).ok();
+
+			if texture.is_none() {
+				missing.push(file);
 			}
+
+			texture
+		};
+
+		// Load each tile texture, keeping a gap for missing ones so the rest stay at their value
+		for i in 1..=SKIN_TILES as u32 {
+			texture_storage.nums.push(load(format!("t{:?}.png", 2i32.pow(i))));
 		}
 
 		// Load blank tile texture
-		if let Ok(texture) = Texture::from_path(&mut ctx, "./assets/tempty.png", Flip::None, &settings) {
-			texture_storage.empty_tile = Some(texture);
+		texture_storage.empty_tile = load("tempty.png".to_string());
+
+		if !missing.is_empty() {
+			println!("Could not load tile images {} from {}, those tiles are drawn instead", missing.join(", "), directory.display());
 		}
 
 		texture_storage
 	}
 }
 
+// Blend from one color to another, `t` going from 0 to 1
+pub fn mix(from: Rgba, to: Rgba, t: f32) -> Rgba {
+	let mut color = from;
+
+	for (channel, target) in color.iter_mut().zip(to.iter()) {
+		*channel += (target - *channel) * t;
+	}
+
+	color
+}
+
+// Draw text with a transform in layout coordinates scaled by `scale`. The glyphs are rendered at the
+// scaled font size instead of being stretched, so they stay sharp.
+pub fn scaled_text(color: Rgba, font_size: u32, input_text: &str, glyphs: &mut Glyphs, transform: [[f64; 3]; 2], scale: f64, gl: &mut G2d) {
+	let scaled_size = ((font_size as f64 * scale).round() as u32).max(1);
+	let transform = transform.scale(1.0 / scale, 1.0 / scale);
+
+	text(color, scaled_size, input_text, glyphs, transform, gl)
+		.expect("Failed to draw text");
+}
+
 // Draw text as multiple lines by splitting by '\n' and drawing each split
-pub fn multi_line_text(color: Rgba, font_size: u32, input_text: &str, glyphs: &mut Glyphs, transform: [[f64; 3]; 2], gl: &mut G2d) {
+pub fn multi_line_text(color: Rgba, font_size: u32, input_text: &str, glyphs: &mut Glyphs, transform: [[f64; 3]; 2], scale: f64, gl: &mut G2d) {
 	// Get separate lines of text
 	let text_lines: Vec<&str> = input_text.split('\n').collect();
 
@@ -146,8 +251,7 @@ pub fn multi_line_text(color: Rgba, font_size: u32, input_text: &str, glyphs: &m
 
 	// Draw each separate text line
 	for line in text_lines {
-		text(color, font_size, line, glyphs, new_transform, gl)
-			.expect("Failed to draw text");
+		scaled_text(color, font_size, line, glyphs, new_transform, scale, gl);
 
 		// Move line down
 		new_transform = new_transform
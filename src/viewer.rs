@@ -0,0 +1,125 @@
+use twenty::{engine::Board, replay::Replay};
+
+use crate::playboard::PlayBoard;
+
+// Moves per second a replay starts at, and the slowest and fastest it can be played
+const DEFAULT_SPEED: f64 = 4.0;
+const MIN_SPEED: f64 = 0.5;
+const MAX_SPEED: f64 = 64.0;
+
+// Plays a recorded game back on a `PlayBoard`, one animated move at a time
+pub struct ReplayViewer {
+	replay: Replay,
+	// Number of moves shown so far
+	position: usize,
+	paused: bool,
+	// Moves per second
+	speed: f64,
+	// Seconds since the last move
+	timer: f64
+}
+
+impl ReplayViewer {
+	pub fn new(replay: Replay) -> Self {
+		ReplayViewer {
+			replay,
+			position: 0,
+			paused: false,
+			speed: DEFAULT_SPEED,
+			timer: 0.0
+		}
+	}
+
+	// View of the board before the first move
+	pub fn start(&self) -> PlayBoard {
+		ReplayViewer::view(self.replay.initial().clone())
+	}
+
+	// View of a board from the replay, which doesn't count as the player's game
+	fn view(board: Board) -> PlayBoard {
+		let mut view = PlayBoard::with_board(board);
+		view.set_playback(true);
+		view
+	}
+
+	// Play the next move once the last one has finished animating
+	pub fn update(&mut self, board: &mut PlayBoard, dt: f64) {
+		self.skip_win(board);
+
+		if self.paused || !board.is_idle() { return; }
+
+		self.timer += dt;
+
+		if self.timer >= 1.0 / self.speed {
+			self.timer = 0.0;
+			self.step_forward(board);
+		}
+	}
+
+	// Show the next move, returns false at the end of the replay or while a move is animating
+	pub fn step_forward(&mut self, board: &mut PlayBoard) -> bool {
+		self.skip_win(board);
+
+		match self.replay.steps().get(self.position) {
+			Some(step) if board.is_idle() => {
+				board.replay_step(step);
+				self.position += 1;
+				true
+			},
+			_ => false
+		}
+	}
+
+	// Jump back one move without animating
+	pub fn step_back(&mut self, board: &mut PlayBoard) {
+		if self.position > 0 {
+			self.seek(board, self.position - 1);
+		}
+	}
+
+	// Jump to the board after the given number of moves
+	pub fn seek(&mut self, board: &mut PlayBoard, position: usize) {
+		let mut view = ReplayViewer::view(self.replay.board_at(position));
+		view.set_highscore(board.highscore());
+		view.set_animation_speed(board.animation_speed());
+
+		*board = view;
+		self.position = position;
+		self.timer = 0.0;
+		self.skip_win(board);
+	}
+
+	pub fn toggle_pause(&mut self) {
+		self.paused = !self.paused;
+		self.timer = 0.0;
+	}
+
+	pub fn pause(&mut self) {
+		self.paused = true;
+	}
+
+	pub fn faster(&mut self) {
+		self.speed = (self.speed * 2.0).min(MAX_SPEED);
+	}
+
+	pub fn slower(&mut self) {
+		self.speed = (self.speed / 2.0).max(MIN_SPEED);
+	}
+
+	// Current move, number of moves, whether the replay is paused and its speed
+	pub fn status(&self) -> String {
+		format!(
+			"Move {}/{}\n{}",
+			self.position,
+			self.replay.len(),
+			if self.paused { "Paused".to_string() } else { format!("{} moves/s", self.speed) }
+		)
+	}
+
+	// Replays always continue past the winning tile, they never stop on the win overlay
+	fn skip_win(&self, board: &mut PlayBoard) {
+		if board.is_won() {
+			board.keep_playing();
+		}
+	}
+}
@@ -0,0 +1,137 @@
+use rand::Rng;
+use std::sync::Arc;
+
+use twenty::{
+	bitboard::Bitboard,
+	engine::{Board, Direction, EMPTY},
+	rng::GameRng,
+	spawn::WeightedSpawn
+};
+
+const BOARDS: u64 = 20_000;
+
+// Random 4x4 board with roughly half of the cells filled, using small values so merges are common
+fn random_board(rng: &mut GameRng, max_value: usize) -> Board {
+	let cells = (0..4)
+		.map(|_| {
+			(0..4)
+				.map(|_| if rng.gen_bool(0.5) { EMPTY } else { rng.gen_range(0, max_value + 1) })
+				.collect()
+		})
+		.collect();
+
+	Board::with_state(cells, 0, 0, 0, GameRng::from_seed(0))
+}
+
+#[test]
+fn slides_match_engine() {
+	let mut rng = GameRng::from_seed(1);
+
+	for _ in 0..BOARDS {
+		let max_value = if rng.gen_bool(0.5) { 3 } else { Bitboard::MAX_VALUE - 1 };
+		let board = random_board(&mut rng, max_value);
+		let bitboard = Bitboard::from_board(&board).unwrap();
+
+		for &direction in &Direction::ALL {
+			let mut expected = board.clone();
+			let outcome = expected.slide(direction);
+			let (slid, score) = bitboard.slide(direction);
+
+			assert_eq!(&slid.to_cells(), expected.cells(), "{:?} slide of {:?}", direction, board.cells());
+			assert_eq!(score, outcome.score, "{:?} slide of {:?}", direction, board.cells());
+			assert_eq!(slid != bitboard, outcome.moved(), "{:?} slide of {:?}", direction, board.cells());
+		}
+
+		assert_eq!(bitboard.is_game_over(), board.is_game_over());
+		assert_eq!(bitboard.max_tile(), board.max_tile());
+		assert_eq!(bitboard.empty_cells().len(), board.empty_cells().len());
+	}
+}
+
+#[test]
+fn full_games_match_engine() {
+	for seed in 0..50 {
+		let mut board = Board::with_seed(4, 4, seed);
+		let mut moves = GameRng::from_seed(seed);
+		board.spawn_tile();
+
+		while !board.is_game_over() {
+			let direction = Direction::ALL[moves.gen_range(0, 4)];
+			let (slid, score) = Bitboard::from_board(&board).unwrap().slide(direction);
+			let outcome = board.slide(direction);
+
+			assert_eq!(&slid.to_cells(), board.cells());
+			assert_eq!(score, outcome.score);
+
+			if outcome.moved() {
+				board.spawn_tile();
+			}
+		}
+	}
+}
+
+#[test]
+fn play_matches_engine() {
+	for seed in 0..50 {
+		let mut board = Board::with_seed(4, 4, seed);
+		board.spawn_tile();
+
+		let mut bitboard = Bitboard::from_board(&board).unwrap();
+		let mut rng = board.rng().clone();
+		let mut moves = GameRng::from_seed(seed);
+
+		while !board.is_game_over() {
+			let direction = Direction::ALL[moves.gen_range(0, 4)];
+			let outcome = board.play(direction);
+			let played = bitboard.play(direction, board.spawn_policy(), &mut rng);
+
+			assert_eq!(played, if outcome.moved() { Some((outcome.score, outcome.spawned)) } else { None });
+			assert_eq!(&bitboard.to_cells(), board.cells());
+			assert_eq!(&rng, board.rng());
+		}
+
+		assert!(bitboard.is_game_over());
+	}
+}
+
+#[test]
+fn spawns_match_engine() {
+	let policy = WeightedSpawn::parse("2:1, 4:1, 8:1").unwrap();
+
+	for seed in 0..200 {
+		let mut board = Board::with_seed(4, 4, seed);
+		board.set_spawn_policy(Arc::new(policy.clone()));
+
+		let mut bitboard = Bitboard::from_board(&board).unwrap();
+		let mut rng = board.rng().clone();
+
+		for _ in 0..17 {
+			assert_eq!(bitboard.spawn_tile(&policy, &mut rng), board.spawn_tile());
+		}
+
+		assert_eq!(&bitboard.to_cells(), board.cells());
+	}
+}
+
+#[test]
+fn round_trips_cells() {
+	let mut rng = GameRng::from_seed(2);
+
+	for _ in 0..1000 {
+		let board = random_board(&mut rng, Bitboard::MAX_VALUE);
+		let bitboard = Bitboard::from_board(&board).unwrap();
+
+		assert_eq!(&bitboard.to_cells(), board.cells());
+		assert_eq!(Bitboard::from_raw(bitboard.raw()), bitboard);
+	}
+}
+
+#[test]
+fn unrepresentable_boards_are_rejected() {
+	assert_eq!(Bitboard::from_board(&Board::new(5, 4)), None);
+	assert_eq!(Bitboard::from_board(&Board::new(4, 3)), None);
+
+	let mut board = Board::new(4, 4);
+	board.set(2, 1, Bitboard::MAX_VALUE + 1);
+	assert_eq!(Bitboard::from_board(&board), None);
+}
@@ -0,0 +1,109 @@
+use twenty::{
+	engine::Board,
+	rng::GameRng,
+	spawn::{SpawnError, SpawnPolicy, WeightedSpawn}
+};
+
+const SAMPLES: usize = 160_000;
+
+// Chi-squared statistic of observed counts against equal expected counts
+fn chi_squared(counts: &[usize]) -> f64 {
+	let expected = counts.iter().sum::<usize>() as f64 / counts.len() as f64;
+
+	counts.iter()
+		.map(|&count| (count as f64 - expected).powi(2) / expected)
+		.sum()
+}
+
+#[test]
+fn cell_choice_is_uniform() {
+	let policy = WeightedSpawn::classic();
+	let mut rng = GameRng::from_seed(1);
+	let empty: Vec<(usize, usize)> = (0..16).map(|i| (i % 4, i / 4)).collect();
+	let mut counts = vec![0; empty.len()];
+
+	for _ in 0..SAMPLES {
+		counts[policy.choose_cell(&empty, &mut rng)] += 1;
+	}
+
+	// 15 degrees of freedom, p = 0.001
+	assert!(chi_squared(&counts) < 37.7, "biased cell choice: {:?}", counts);
+}
+
+#[test]
+fn first_and_last_cells_are_not_biased() {
+	let policy = WeightedSpawn::classic();
+	let mut rng = GameRng::from_seed(2);
+	let empty = vec![(0, 0), (1, 0), (2, 0)];
+	let mut counts = vec![0; empty.len()];
+
+	for _ in 0..SAMPLES {
+		counts[policy.choose_cell(&empty, &mut rng)] += 1;
+	}
+
+	// 2 degrees of freedom, p = 0.001
+	assert!(chi_squared(&counts) < 13.8, "biased cell choice: {:?}", counts);
+}
+
+#[test]
+fn board_spawns_uniformly_on_empty_board() {
+	let mut counts = vec![0; 16];
+
+	for seed in 0..SAMPLES as u64 {
+		let mut board = Board::with_seed(4, 4, seed);
+		let spawn = board.spawn_tile().unwrap();
+
+		counts[spawn.y * 4 + spawn.x] += 1;
+	}
+
+	assert!(chi_squared(&counts) < 37.7, "biased spawn position: {:?}", counts);
+}
+
+#[test]
+fn classic_values_are_ninety_ten() {
+	let policy = WeightedSpawn::classic();
+	let mut rng = GameRng::from_seed(3);
+	let mut counts = [0; 2];
+
+	for _ in 0..SAMPLES {
+		counts[policy.choose_value(&mut rng)] += 1;
+	}
+
+	let ratio = counts[1] as f64 / SAMPLES as f64;
+	assert!((ratio - 0.1).abs() < 0.005, "4 tile ratio was {}", ratio);
+}
+
+#[test]
+fn custom_weights() {
+	let policy = WeightedSpawn::parse("2:1, 4:1, 8:2").unwrap();
+	assert_eq!(policy.weights(), &[(0, 1), (1, 1), (2, 2)]);
+
+	let mut rng = GameRng::from_seed(4);
+	let mut counts = [0; 3];
+
+	for _ in 0..SAMPLES {
+		counts[policy.choose_value(&mut rng)] += 1;
+	}
+
+	for (count, expected) in counts.iter().zip(&[0.25, 0.25, 0.5]) {
+		let ratio = *count as f64 / SAMPLES as f64;
+		assert!((ratio - expected).abs() < 0.01, "ratios were {:?}", counts);
+	}
+}
+
+#[test]
+fn invalid_weights_are_rejected() {
+	assert_eq!(WeightedSpawn::parse("3:1"), Err(SpawnError::InvalidValue(3)));
+	assert_eq!(WeightedSpawn::parse("1:1"), Err(SpawnError::InvalidValue(1)));
+	assert_eq!(WeightedSpawn::parse("2:0, 4:0"), Err(SpawnError::NoWeights));
+	assert_eq!(WeightedSpawn::parse("2"), Err(SpawnError::Malformed("2".to_string())));
+	assert_eq!(WeightedSpawn::parse("2:1:1"), Err(SpawnError::Malformed("2:1:1".to_string())));
+	assert_eq!(WeightedSpawn::parse("2:4294967295, 4:1"), Err(SpawnError::TotalTooLarge));
+}
+
+#[test]
+fn values_above_the_largest_tile_are_rejected() {
+	assert_eq!(WeightedSpawn::parse("2:1, 2147483648:1"), Err(SpawnError::ValueTooLarge(2147483648)));
+	assert_eq!(WeightedSpawn::parse("268435456:1"), Err(SpawnError::ValueTooLarge(268435456)));
+	assert!(WeightedSpawn::parse("134217728:1").is_ok());
+}